@@ -0,0 +1,771 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub};
+
+use num_traits::Zero;
+
+use crate::AffineTransformMatrix;
+
+/// A coordinate system. Points, vectors and rects of different spaces cannot
+/// be mixed, they must be mapped by a `Transform`.
+pub trait Space: Copy + Debug + Default + PartialEq + 'static {
+    const NAME: &'static str;
+}
+
+/// Relative to the top-left corner of the view port in CSS pixels -- i.e. the
+/// y-axis points downwards.
+///
+/// # See
+/// https://developer.mozilla.org/en-US/docs/Web/CSS/Viewport_concepts
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ViewPort;
+
+impl Space for ViewPort {
+    const NAME: &'static str = "ViewPort";
+}
+
+/// Relative to the top-left corner of the whole document in CSS pixels, i.e.
+/// the view port shifted by the scroll position.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Page;
+
+impl Space for Page {
+    const NAME: &'static str = "Page";
+}
+
+/// The local coordinate system of an HTML element, i.e. relative to the
+/// top-left corner of the element without any CSS transform applied, in CSS
+/// pixels.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ElementLocal;
+
+impl Space for ElementLocal {
+    const NAME: &'static str = "ElementLocal";
+}
+
+/// The user space of an SVG element.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Svg;
+
+impl Space for Svg {
+    const NAME: &'static str = "Svg";
+}
+
+/// The local user space of an SVG element, that is panned and zoomed by its
+/// `transform` attribute -- i.e. the coordinates of its content, which the
+/// `transform` maps to the user space `Svg` of its parent.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SvgLocal;
+
+impl Space for SvgLocal {
+    const NAME: &'static str = "SvgLocal";
+}
+
+/// A 2-dimensional position in the coordinate system `S`
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        bound = "",
+        into = "crate::serialization::PointV1",
+        try_from = "crate::serialization::PointV1"
+    )
+)]
+pub struct Point<S> {
+    pub x: f64,
+    pub y: f64,
+    space: PhantomData<S>,
+}
+
+// Copy regardless of the space, which is only a marker
+impl<S> Clone for Point<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for Point<S> {}
+
+impl<S: Space> Display for Point<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}, {})", S::NAME, self.x, self.y)
+    }
+}
+
+impl<S> Point<S> {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self {
+            x,
+            y,
+            space: PhantomData,
+        }
+    }
+    pub fn origin() -> Self {
+        Self::new(0.0, 0.0)
+    }
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+    /// The vector from the origin to this point
+    pub fn as_vec(&self) -> Vector<S> {
+        Vector::new(self.x, self.y)
+    }
+}
+
+impl<S> Add<Vector<S>> for Point<S> {
+    type Output = Self;
+
+    fn add(self, rhs: Vector<S>) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<S> AddAssign<Vector<S>> for Point<S> {
+    fn add_assign(&mut self, rhs: Vector<S>) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<S> Sub<Vector<S>> for Point<S> {
+    type Output = Self;
+
+    fn sub(self, rhs: Vector<S>) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<S> Sub for Point<S> {
+    type Output = Vector<S>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vector::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+/// Scales the point's distance to the origin.
+impl<S> Mul<f64> for Point<S> {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+/// A 2-dimensional vector in the coordinate system `S`
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        bound = "",
+        into = "crate::serialization::VectorV1",
+        try_from = "crate::serialization::VectorV1"
+    )
+)]
+pub struct Vector<S> {
+    pub x: f64,
+    pub y: f64,
+    space: PhantomData<S>,
+}
+
+impl<S> Clone for Vector<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for Vector<S> {}
+
+impl<S: Space> Display for Vector<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} Vec({}, {})", S::NAME, self.x, self.y)
+    }
+}
+
+impl<S> Vector<S> {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self {
+            x,
+            y,
+            space: PhantomData,
+        }
+    }
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+    pub fn length(&self) -> f64 {
+        f64::hypot(self.x, self.y)
+    }
+}
+
+impl<S> Add for Vector<S> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<S> Sub for Vector<S> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<S> Neg for Vector<S> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl<S> Mul<f64> for Vector<S> {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<S> Div<f64> for Vector<S> {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Self::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl<S> Zero for Vector<S> {
+    fn zero() -> Self {
+        Self::new(0.0, 0.0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.x.is_zero() && self.y.is_zero()
+    }
+}
+
+/// An axis-aligned rect in the coordinate system `S`. Unlike the mathematical
+/// `BoundingRect`, its top < bottom, as the y-axis points downwards.
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        bound = "",
+        into = "crate::serialization::RectV1",
+        try_from = "crate::serialization::RectV1"
+    )
+)]
+pub struct Rect<S> {
+    pub top_left: Point<S>,
+    pub dimensions: Vector<S>,
+}
+
+impl<S> Clone for Rect<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for Rect<S> {}
+
+impl<S: Space> Display for Rect<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} Rect x:{{{}..{}}} ⨯ y:{{{}..{}}}",
+            S::NAME,
+            self.left(),
+            self.right(),
+            self.top(),
+            self.bottom()
+        )
+    }
+}
+
+impl<S> Rect<S> {
+    pub fn new(top_left: Point<S>, dimensions: Vector<S>) -> Self {
+        debug_assert!(dimensions.x >= 0.0);
+        debug_assert!(dimensions.y >= 0.0);
+        Self {
+            top_left,
+            dimensions,
+        }
+    }
+    pub fn top(&self) -> f64 {
+        self.top_left.y
+    }
+    pub fn bottom(&self) -> f64 {
+        self.top() + self.height()
+    }
+    pub fn left(&self) -> f64 {
+        self.top_left.x
+    }
+    pub fn right(&self) -> f64 {
+        self.left() + self.width()
+    }
+    pub fn width(&self) -> f64 {
+        self.dimensions.x
+    }
+    pub fn height(&self) -> f64 {
+        self.dimensions.y
+    }
+    pub fn top_left(&self) -> Point<S> {
+        self.top_left
+    }
+    pub fn bottom_right(&self) -> Point<S> {
+        Point::new(self.right(), self.bottom())
+    }
+    pub fn dimensions(&self) -> Vector<S> {
+        self.dimensions
+    }
+    pub fn center(&self) -> Point<S> {
+        self.top_left + self.dimensions / 2.0
+    }
+
+    /// width / height
+    ///
+    /// Not finite, if the height is zero.
+    pub fn aspect_ratio(&self) -> f64 {
+        self.width() / self.height()
+    }
+
+    /// The smallest rect containing both `corner` and `opposite_corner`.
+    pub fn from_corners(corner: Point<S>, opposite_corner: Point<S>) -> Self {
+        let top_left = Point::new(
+            f64::min(corner.x, opposite_corner.x),
+            f64::min(corner.y, opposite_corner.y),
+        );
+        let bottom_right = Point::new(
+            f64::max(corner.x, opposite_corner.x),
+            f64::max(corner.y, opposite_corner.y),
+        );
+        Self::new(top_left, bottom_right - top_left)
+    }
+
+    /// Whether the rect has no area, i.e. its width or height is zero.
+    ///
+    /// Empty rects still have a position: They may contain points on their
+    /// border and intersect other rects.
+    pub fn is_empty(&self) -> bool {
+        self.width() <= 0.0 || self.height() <= 0.0
+    }
+
+    /// Whether `point` is inside the rect or on its border.
+    pub fn contains(&self, point: Point<S>) -> bool {
+        (self.left()..=self.right()).contains(&point.x)
+            && (self.top()..=self.bottom()).contains(&point.y)
+    }
+
+    /// Whether `other` is inside this rect, including its border.
+    pub fn contains_rect(&self, other: &Self) -> bool {
+        self.contains(other.top_left) && self.contains(other.bottom_right())
+    }
+
+    /// Returns the rect both rects have in common, or `None`, if they are
+    /// disjoint. Rects touching at an edge or a corner intersect in an empty
+    /// rect.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let top_left = Point::new(
+            f64::max(self.left(), other.left()),
+            f64::max(self.top(), other.top()),
+        );
+        let bottom_right = Point::new(
+            f64::min(self.right(), other.right()),
+            f64::min(self.bottom(), other.bottom()),
+        );
+        if top_left.x > bottom_right.x || top_left.y > bottom_right.y {
+            None
+        } else {
+            Some(Self::new(top_left, bottom_right - top_left))
+        }
+    }
+
+    /// Returns the smallest rect containing both rects -- including empty
+    /// ones, which are treated as points or lines.
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_corners(
+            Point::new(
+                f64::min(self.left(), other.left()),
+                f64::min(self.top(), other.top()),
+            ),
+            Point::new(
+                f64::max(self.right(), other.right()),
+                f64::max(self.bottom(), other.bottom()),
+            ),
+        )
+    }
+
+    /// Moves each edge outwards by `margin` in its dimension, keeping the
+    /// center. A negative margin shrinks the rect, at most to an empty rect at
+    /// the center.
+    pub fn inflate(&self, margin: Vector<S>) -> Self {
+        let dimensions = Vector::new(
+            f64::max(self.width() + 2.0 * margin.x, 0.0),
+            f64::max(self.height() + 2.0 * margin.y, 0.0),
+        );
+        Self::new(self.center() - dimensions / 2.0, dimensions)
+    }
+
+    /// Moves each edge inwards by `margin` in its dimension, see `inflate`.
+    pub fn deflate(&self, margin: Vector<S>) -> Self {
+        self.inflate(-margin)
+    }
+
+    /// Returns the point of the rect -- including its border -- closest to
+    /// `point`.
+    pub fn clamp_point(&self, point: Point<S>) -> Point<S> {
+        Point::new(
+            point.x.clamp(self.left(), self.right()),
+            point.y.clamp(self.top(), self.bottom()),
+        )
+    }
+
+    /// Scales the rect by `factor`, such that `fix_point` remains at the same
+    /// position relative to it.
+    pub fn scale_about(&self, fix_point: Point<S>, factor: f64) -> Self {
+        debug_assert!(factor >= 0.0);
+        Self::new(
+            fix_point + (self.top_left - fix_point) * factor,
+            self.dimensions * factor,
+        )
+    }
+
+    /// Returns the rect `t` of the way from `self` to `target`, interpolating
+    /// position and size linearly, i.e. `self` for `t == 0.0` and `target`
+    /// for `t == 1.0`.
+    pub fn lerp(&self, target: &Self, t: f64) -> Self {
+        Self::new(
+            self.top_left + (target.top_left - self.top_left) * t,
+            self.dimensions + (target.dimensions - self.dimensions) * t,
+        )
+    }
+
+    /// Returns the vector from the top-left corner of this rect to `point`.
+    pub fn offset(&self, point: Point<S>) -> Vector<S> {
+        point - self.top_left
+    }
+
+    /// Returns a vector with each offset dimension divided by the rect-size in
+    /// the respective dimension.
+    ///
+    /// `point`s inside the rect thus have values in each dimensions between
+    /// `0.0` and `1.0`.
+    pub fn rect_size_relative_offset(&self, point: Point<S>) -> Vector<S> {
+        let absolute_offset = self.offset(point);
+        Vector::new(
+            absolute_offset.x / self.width(),
+            absolute_offset.y / self.height(),
+        )
+    }
+}
+
+/// An affine transformation from the coordinate system `From` to `To`.
+///
+/// Transforms can only be applied to points and vectors of `From` and only be
+/// chained, if the spaces match.
+#[derive(Debug, PartialEq)]
+pub struct Transform<From, To> {
+    matrix: AffineTransformMatrix,
+    spaces: PhantomData<(From, To)>,
+}
+
+impl<From, To> Clone for Transform<From, To> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<From, To> Copy for Transform<From, To> {}
+
+impl<S> Transform<S, S> {
+    pub fn identity() -> Self {
+        Self::new(AffineTransformMatrix::identity())
+    }
+}
+
+impl<From, To> Transform<From, To> {
+    /// `matrix` must map `From` to `To`, which cannot be checked.
+    pub fn new(matrix: AffineTransformMatrix) -> Self {
+        Self {
+            matrix,
+            spaces: PhantomData,
+        }
+    }
+
+    pub fn matrix(&self) -> AffineTransformMatrix {
+        self.matrix
+    }
+
+    pub fn apply(&self, point: Point<From>) -> Point<To> {
+        let (x, y) = self.matrix.apply_to_point(point.x, point.y);
+        Point::new(x, y)
+    }
+
+    /// Unlike points, vectors are not affected by the translation part of the
+    /// transformation.
+    pub fn apply_to_vector(&self, vector: Vector<From>) -> Vector<To> {
+        let (x, y) = self.matrix.apply_to_vector(vector.x, vector.y);
+        Vector::new(x, y)
+    }
+
+    /// Returns the axis-aligned bounding rect of the four transformed corners
+    /// of `rect`. It is larger than the transformed rect itself, if the
+    /// transformation rotates or skews.
+    pub fn apply_to_rect(&self, rect: Rect<From>) -> Rect<To> {
+        let corners = [
+            rect.top_left(),
+            Point::new(rect.right(), rect.top()),
+            Point::new(rect.left(), rect.bottom()),
+            rect.bottom_right(),
+        ]
+        .map(|corner| self.apply(corner));
+        corners[1..].iter().fold(
+            Rect::new(corners[0], Vector::new(0.0, 0.0)),
+            |bounds, corner| bounds.union(&Rect::new(*corner, Vector::new(0.0, 0.0))),
+        )
+    }
+
+    pub fn try_inverse(&self) -> Option<Transform<To, From>> {
+        self.matrix.try_inverse().map(Transform::new)
+    }
+
+    /// Returns the transformation, that applies `self` first, then `next`.
+    pub fn then<Next>(&self, next: Transform<To, Next>) -> Transform<From, Next> {
+        Transform::new(next.matrix * self.matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestRect = Rect<ViewPort>;
+
+    fn rect(left: f64, top: f64, width: f64, height: f64) -> TestRect {
+        Rect::new(Point::new(left, top), Vector::new(width, height))
+    }
+
+    #[test]
+    fn is_empty() {
+        assert!(!rect(0.0, 0.0, 1.0, 1.0).is_empty());
+        assert!(rect(0.0, 0.0, 0.0, 1.0).is_empty());
+        assert!(rect(0.0, 0.0, 1.0, 0.0).is_empty());
+        assert!(rect(5.0, 5.0, 0.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn intersection() {
+        let square = rect(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(
+            square.intersection(&rect(5.0, 5.0, 10.0, 10.0)),
+            Some(rect(5.0, 5.0, 5.0, 5.0))
+        );
+        assert_eq!(square.intersection(&rect(20.0, 0.0, 10.0, 10.0)), None);
+    }
+
+    #[test]
+    fn intersection_of_touching_rects_is_empty() {
+        let square = rect(0.0, 0.0, 10.0, 10.0);
+        let at_edge = square.intersection(&rect(10.0, 2.0, 5.0, 5.0)).unwrap();
+        assert_eq!(at_edge, rect(10.0, 2.0, 0.0, 5.0));
+        assert!(at_edge.is_empty());
+        let at_corner = square.intersection(&rect(10.0, 10.0, 5.0, 5.0)).unwrap();
+        assert_eq!(at_corner, rect(10.0, 10.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn intersection_with_empty_rects() {
+        let square = rect(0.0, 0.0, 10.0, 10.0);
+        // zero width
+        assert_eq!(
+            square.intersection(&rect(5.0, -5.0, 0.0, 10.0)),
+            Some(rect(5.0, 0.0, 0.0, 5.0))
+        );
+        // zero height
+        assert_eq!(
+            square.intersection(&rect(-5.0, 5.0, 10.0, 0.0)),
+            Some(rect(0.0, 5.0, 5.0, 0.0))
+        );
+        // zero width and height
+        assert_eq!(
+            square.intersection(&rect(10.0, 10.0, 0.0, 0.0)),
+            Some(rect(10.0, 10.0, 0.0, 0.0))
+        );
+        assert_eq!(square.intersection(&rect(11.0, 5.0, 0.0, 0.0)), None);
+        assert_eq!(square.intersection(&rect(5.0, 11.0, 0.0, 10.0)), None);
+    }
+
+    #[test]
+    fn union_with_empty_rects() {
+        let square = rect(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(square.union(&rect(5.0, 5.0, 0.0, 0.0)), square);
+        // an empty rect outside is treated as a point or a line
+        assert_eq!(
+            square.union(&rect(20.0, 5.0, 0.0, 0.0)),
+            rect(0.0, 0.0, 20.0, 10.0)
+        );
+        assert_eq!(
+            square.union(&rect(-5.0, 5.0, 0.0, 20.0)),
+            rect(-5.0, 0.0, 15.0, 25.0)
+        );
+        assert_eq!(
+            rect(1.0, 1.0, 0.0, 0.0).union(&rect(3.0, 4.0, 0.0, 0.0)),
+            rect(1.0, 1.0, 2.0, 3.0)
+        );
+    }
+
+    #[test]
+    fn inflate_and_deflate() {
+        let square = rect(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(
+            square.inflate(Vector::new(1.0, 2.0)),
+            rect(-1.0, -2.0, 12.0, 14.0)
+        );
+        assert_eq!(
+            square.deflate(Vector::new(1.0, 2.0)),
+            rect(1.0, 2.0, 8.0, 6.0)
+        );
+    }
+
+    #[test]
+    fn inflate_by_a_negative_margin_clamps_to_the_center() {
+        let square = rect(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(
+            square.inflate(Vector::new(-20.0, -1.0)),
+            rect(5.0, 1.0, 0.0, 8.0)
+        );
+        assert_eq!(
+            square.inflate(Vector::new(-20.0, -20.0)),
+            rect(5.0, 5.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn contains_rect() {
+        let square = rect(0.0, 0.0, 10.0, 10.0);
+        assert!(square.contains_rect(&square));
+        assert!(square.contains_rect(&rect(2.0, 2.0, 5.0, 5.0)));
+        assert!(!square.contains_rect(&rect(8.0, 8.0, 5.0, 5.0)));
+        // empty rects are contained, if their position is
+        assert!(square.contains_rect(&rect(5.0, 5.0, 0.0, 0.0)));
+        assert!(square.contains_rect(&rect(10.0, 0.0, 0.0, 10.0)));
+        assert!(!square.contains_rect(&rect(11.0, 5.0, 0.0, 0.0)));
+        // an empty rect contains only empty rects at its position
+        let point = rect(5.0, 5.0, 0.0, 0.0);
+        assert!(point.contains_rect(&point));
+        assert!(!point.contains_rect(&square));
+    }
+
+    #[test]
+    fn clamp_point() {
+        let square = rect(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(
+            square.clamp_point(Point::new(5.0, 5.0)),
+            Point::new(5.0, 5.0)
+        );
+        assert_eq!(
+            square.clamp_point(Point::new(-5.0, 15.0)),
+            Point::new(0.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn scale_about() {
+        let square = rect(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(
+            square.scale_about(Point::new(10.0, 0.0), 0.5),
+            rect(5.0, 0.0, 5.0, 5.0)
+        );
+        assert_eq!(
+            square.scale_about(Point::new(5.0, 5.0), 2.0),
+            rect(-5.0, -5.0, 20.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn lerp() {
+        let start = rect(0.0, 0.0, 10.0, 10.0);
+        let target = rect(10.0, 20.0, 0.0, 30.0);
+        assert_eq!(start.lerp(&target, 0.0), start);
+        assert_eq!(start.lerp(&target, 0.5), rect(5.0, 10.0, 5.0, 20.0));
+        assert_eq!(start.lerp(&target, 1.0), target);
+    }
+
+    fn assert_rect_eq<S>(actual: Rect<S>, expected: Rect<S>) {
+        for (actual, expected) in [
+            (actual.left(), expected.left()),
+            (actual.top(), expected.top()),
+            (actual.width(), expected.width()),
+            (actual.height(), expected.height()),
+        ] {
+            assert_relative_eq!(actual, expected, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn apply_to_rect_bounds_a_rotated_rect() {
+        let rotation: Transform<ViewPort, Svg> =
+            Transform::new(AffineTransformMatrix::rotation(std::f64::consts::FRAC_PI_4));
+        let bounds = rotation.apply_to_rect(rect(0.0, 0.0, 10.0, 10.0));
+        let diagonal = 10.0 * std::f64::consts::SQRT_2;
+        assert_rect_eq(
+            bounds,
+            Rect::new(
+                Point::new(-diagonal / 2.0, 0.0),
+                Vector::new(diagonal, diagonal),
+            ),
+        );
+        // the bounding rect is larger than the rotated square
+        assert!(bounds.width() * bounds.height() > 100.0);
+    }
+
+    #[test]
+    fn apply_to_rect_bounds_a_skewed_rect() {
+        let skew: Transform<ViewPort, Svg> =
+            Transform::new(AffineTransformMatrix::skew_x(std::f64::consts::FRAC_PI_4));
+        // the bottom edge moves right by the height
+        assert_rect_eq(
+            skew.apply_to_rect(rect(0.0, 0.0, 10.0, 5.0)),
+            Rect::new(Point::new(0.0, 0.0), Vector::new(15.0, 5.0)),
+        );
+    }
+
+    #[test]
+    fn apply_to_rect_of_a_flip_has_positive_dimensions() {
+        let flip: Transform<ViewPort, Svg> =
+            Transform::new(AffineTransformMatrix::scaling(-1.0, 2.0));
+        assert_eq!(
+            flip.apply_to_rect(rect(1.0, 2.0, 3.0, 4.0)),
+            Rect::new(Point::new(-4.0, 4.0), Vector::new(3.0, 8.0))
+        );
+    }
+
+    #[test]
+    fn apply_to_rect_and_back() {
+        let transform: Transform<ViewPort, Svg> = Transform::new(
+            AffineTransformMatrix::translation(5.0, -3.0)
+                * AffineTransformMatrix::scaling(2.0, 0.5),
+        );
+        let square = rect(1.0, 2.0, 4.0, 8.0);
+        let transformed = transform.apply_to_rect(square);
+        assert_rect_eq(
+            transformed,
+            Rect::new(Point::new(7.0, -2.0), Vector::new(8.0, 4.0)),
+        );
+        let inverse = transform.try_inverse().unwrap();
+        assert_rect_eq(inverse.apply_to_rect(transformed), square);
+    }
+}
@@ -4,22 +4,20 @@
 extern crate approx;
 #[macro_use]
 extern crate log;
-#[macro_use]
 extern crate zoon;
 use svg::ViewBox;
 use web::pan_z::*;
-use web::IntoElementWithAttributeSignal;
 use zoon::*;
+mod geom;
+#[cfg(feature = "serde")]
+mod serialization;
 mod svg;
 mod web;
 // ------ ------
 //    States
 // ------ ------
 
-#[static_ref]
-pub fn view_box() -> &'static Mutable<ViewBox> {
-    Mutable::new(ViewBox::default())
-}
+// Each viewer owns its states, see `viewer`.
 
 // ------ ------
 //   Commands
@@ -31,37 +29,68 @@ pub fn view_box() -> &'static Mutable<ViewBox> {
 
 fn root() -> RawHtmlEl {
     RawHtmlEl::new("div").children([
-        //enable_zooming_html_element(RawHtmlEl::new("article").child(Text::new("bla bla bla")),view_state())
-        unsafe { enable_zooming_svg_view_box(four_circles(), view_box()) },
+        //PanZoomHandle::html_element(RawHtmlEl::new("article").child(Text::new("bla bla bla")),Mutable::new(ViewState::new()), None).1
+        viewer("my_svg_element"),
+        viewer("my_other_svg_element"),
     ])
 }
 
-fn four_circles() -> RawSvgEl {
-    view_box()
-        .signal()
-        .into_element_with_attribute_signal((), "my_svg_element", None)
-        .children([
-            RawSvgEl::new("circle")
-                .attr("cx", "-30")
-                .attr("cy", "-30")
-                .attr("r", "10")
-                .attr("fill", "cadetblue"),
-            RawSvgEl::new("circle")
-                .attr("cx", "30")
-                .attr("cy", "30")
-                .attr("r", "10")
-                .attr("fill", "steelblue"),
-            RawSvgEl::new("circle")
-                .attr("cx", "30")
-                .attr("cy", "-30")
-                .attr("r", "10")
-                .attr("fill", "lightblue"),
-            RawSvgEl::new("circle")
-                .attr("cx", "-30")
-                .attr("cy", "30")
-                .attr("r", "10")
-                .attr("fill", "cornflowerblue"),
-        ])
+/// An independent pan-zoom viewer of the four circles, filling half of the
+/// window's height, with a minimap in its corner.
+fn viewer(id: &str) -> RawHtmlEl {
+    let view_box = ViewBox::default();
+    // survives reloads, e.g. by mzoon, but not closing the tab
+    #[cfg(feature = "persistence")]
+    let view_box = restore_view(StorageKind::Session, id, &view_box).unwrap_or(view_box);
+    let view_box = Mutable::new(view_box);
+    let (_view_box_handle, svg) =
+        PanZoomHandle::svg_view_box(view_box.clone(), id, None, None, four_circles);
+    #[cfg(feature = "persistence")]
+    let svg = persist_view(svg, view_box.clone(), StorageKind::Session, id, 300);
+    // a view in the URL takes precedence over the persisted one
+    let svg = deep_link(svg, view_box.clone(), id, 300);
+    let svg = ViewHistory::new(view_box.clone(), 100).bind(svg, 300);
+    // an overview in the bottom right corner, above the view
+    let overview = RawHtmlEl::new("div")
+        .style("position", "absolute")
+        .style("right", "8px")
+        .style("bottom", "8px")
+        .style("width", "20%")
+        .style("height", "25%")
+        .style("background", "white")
+        .style("border", "1px solid gray")
+        .child(minimap(view_box.clone(), four_circles));
+    let (_view_port_sync, svg) = sync_view_box_with_view_port(svg, view_box);
+    RawHtmlEl::new("div")
+        .style("position", "relative")
+        .style("height", "50vh")
+        .child(svg)
+        .child(overview)
+}
+
+fn four_circles(svg: RawSvgEl) -> RawSvgEl {
+    svg.children([
+        RawSvgEl::new("circle")
+            .attr("cx", "-30")
+            .attr("cy", "-30")
+            .attr("r", "10")
+            .attr("fill", "cadetblue"),
+        RawSvgEl::new("circle")
+            .attr("cx", "30")
+            .attr("cy", "30")
+            .attr("r", "10")
+            .attr("fill", "steelblue"),
+        RawSvgEl::new("circle")
+            .attr("cx", "30")
+            .attr("cy", "-30")
+            .attr("r", "10")
+            .attr("fill", "lightblue"),
+        RawSvgEl::new("circle")
+            .attr("cx", "-30")
+            .attr("cy", "30")
+            .attr("r", "10")
+            .attr("fill", "cornflowerblue"),
+    ])
 }
 
 pub fn setup_logger() -> Result<(), fern::InitError> {
@@ -0,0 +1,750 @@
+//! The stable, versioned serde representations of the pan-zoom states.
+//!
+//! `ViewBox`, `ViewState` and `ElementTransform` are (de)serialized through
+//! these representations, so their fields can change without breaking saved
+//! views. Each representation is tagged with its `"version"`, e.g.
+//!
+//! ```json
+//! {"version": "1", "top_left": {"x": 0.0, "y": 0.0}, "scale": 1.0, ...}
+//! ```
+//!
+//! A released version must never change. A new version is added as a new
+//! variant; the states keep deserializing all older variants. Thus the nested
+//! values of a version are frozen types of their own, e.g. `LimitsV1`, which
+//! do not follow changes of the live types, e.g. `PanZoomLimits`.
+//!
+//! Deserializing validates the values: A state, that could not be shown --
+//! e.g. with a scale of zero, an empty view box or limits with a minimum
+//! scale above the maximum -- is rejected with an `InvalidViewErr`. Scales
+//! beyond the limits are clamped to them.
+//!
+//! Geometry types -- points, vectors, rects, limits, `PreserveAspectRatio`,
+//! matrices and their decompositions -- go through frozen representations as
+//! well, e.g. a point through `PointV1` as `{"x": 1.0, "y": 2.0}`. They are
+//! plain values, so they carry no version tag; a changed representation
+//! would be a new type, which only new states use. Deserializing rejects
+//! values, that are not finite. The coordinate space of a point is not
+//! serialized. To serialize a `Transform`, serialize its `matrix()`.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::geom::{Point, Rect, Vector};
+use crate::svg::{Alignment, AxisAlignment, MeetOrSlice, PreserveAspectRatio};
+use crate::{AffineTransformMatrix, Decomposition, PanBounds, PanZoomLimits};
+
+/// Version 2 adds the rotation of the content, in radians.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "version")]
+pub(crate) enum ViewBoxRepr {
+    #[serde(rename = "1")]
+    V1 {
+        view_box: RectV1,
+        content_box: RectV1,
+        limits: LimitsV1,
+        preserve_aspect_ratio: PreserveAspectRatioV1,
+    },
+    #[serde(rename = "2")]
+    V2 {
+        view_box: RectV1,
+        content_box: RectV1,
+        rotation: f64,
+        limits: LimitsV1,
+        preserve_aspect_ratio: PreserveAspectRatioV1,
+    },
+}
+
+/// Angles are in radians.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "version")]
+pub(crate) enum ViewStateRepr {
+    #[serde(rename = "1")]
+    V1 {
+        top_left: PointV1,
+        scale: f64,
+        rotation: f64,
+        limits: LimitsV1,
+    },
+}
+
+/// Angles are in radians, unlike the `transform` attribute.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "version")]
+pub(crate) enum ElementTransformRepr {
+    #[serde(rename = "1")]
+    V1 {
+        translate: VectorV1,
+        scale: f64,
+        rotation: f64,
+        limits: LimitsV1,
+    },
+}
+
+/// Why a deserialized state cannot be shown
+#[derive(Debug, Clone)]
+pub(crate) struct InvalidViewErr(String);
+
+impl InvalidViewErr {
+    pub(crate) fn new(reason: String) -> Self {
+        Self(reason)
+    }
+}
+
+impl Display for InvalidViewErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid view: {}", self.0)
+    }
+}
+
+impl Error for InvalidViewErr {}
+
+/// Returns `scale`, if it is finite and positive.
+pub(crate) fn validate_scale(scale: f64) -> Result<f64, InvalidViewErr> {
+    if scale.is_finite() && scale > 0.0 {
+        Ok(scale)
+    } else {
+        Err(InvalidViewErr(format!(
+            "scale {} is not finite and positive",
+            scale
+        )))
+    }
+}
+
+/// Returns `angle`, if it is finite.
+pub(crate) fn validate_rotation(angle: f64) -> Result<f64, InvalidViewErr> {
+    if angle.is_finite() {
+        Ok(angle)
+    } else {
+        Err(InvalidViewErr(format!("rotation {} is not finite", angle)))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PointV1 {
+    x: f64,
+    y: f64,
+}
+
+impl<S> From<Point<S>> for PointV1 {
+    fn from(point: Point<S>) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+impl<S> TryFrom<PointV1> for Point<S> {
+    type Error = InvalidViewErr;
+
+    fn try_from(point: PointV1) -> Result<Self, Self::Error> {
+        if point.x.is_finite() && point.y.is_finite() {
+            Ok(Point::new(point.x, point.y))
+        } else {
+            Err(InvalidViewErr(format!("{:?} is not finite", point)))
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct VectorV1 {
+    x: f64,
+    y: f64,
+}
+
+impl<S> From<Vector<S>> for VectorV1 {
+    fn from(vector: Vector<S>) -> Self {
+        Self {
+            x: vector.x,
+            y: vector.y,
+        }
+    }
+}
+
+impl<S> TryFrom<VectorV1> for Vector<S> {
+    type Error = InvalidViewErr;
+
+    fn try_from(vector: VectorV1) -> Result<Self, Self::Error> {
+        if vector.x.is_finite() && vector.y.is_finite() {
+            Ok(Vector::new(vector.x, vector.y))
+        } else {
+            Err(InvalidViewErr(format!("{:?} is not finite", vector)))
+        }
+    }
+}
+
+/// The dimensions are never negative, but may be zero.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RectV1 {
+    top_left: PointV1,
+    dimensions: VectorV1,
+}
+
+impl<S> From<Rect<S>> for RectV1 {
+    fn from(rect: Rect<S>) -> Self {
+        Self {
+            top_left: rect.top_left().into(),
+            dimensions: rect.dimensions().into(),
+        }
+    }
+}
+
+impl<S> TryFrom<RectV1> for Rect<S> {
+    type Error = InvalidViewErr;
+
+    fn try_from(rect: RectV1) -> Result<Self, Self::Error> {
+        let dimensions: Vector<S> = rect.dimensions.try_into()?;
+        if dimensions.x < 0.0 || dimensions.y < 0.0 {
+            return Err(InvalidViewErr(format!(
+                "{:?} has negative dimensions",
+                rect
+            )));
+        }
+        Ok(Rect::new(rect.top_left.try_into()?, dimensions))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LimitsV1 {
+    min_scale: f64,
+    max_scale: f64,
+    pan_bounds: PanBoundsV1,
+}
+
+impl From<PanZoomLimits> for LimitsV1 {
+    fn from(limits: PanZoomLimits) -> Self {
+        Self {
+            min_scale: limits.min_scale,
+            max_scale: limits.max_scale,
+            pan_bounds: limits.pan_bounds.into(),
+        }
+    }
+}
+
+/// See `PanZoomLimits::is_valid`
+impl TryFrom<LimitsV1> for PanZoomLimits {
+    type Error = InvalidViewErr;
+
+    fn try_from(limits: LimitsV1) -> Result<Self, Self::Error> {
+        let pan_zoom_limits = PanZoomLimits {
+            min_scale: limits.min_scale,
+            max_scale: limits.max_scale,
+            pan_bounds: limits.pan_bounds.try_into()?,
+        };
+        if pan_zoom_limits.is_valid() {
+            Ok(pan_zoom_limits)
+        } else {
+            Err(InvalidViewErr(format!("{}", pan_zoom_limits)))
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum PanBoundsV1 {
+    Unbounded,
+    KeepContentVisible { min_visible_fraction: f64 },
+}
+
+impl From<PanBounds> for PanBoundsV1 {
+    fn from(pan_bounds: PanBounds) -> Self {
+        match pan_bounds {
+            PanBounds::Unbounded => Self::Unbounded,
+            PanBounds::KeepContentVisible {
+                min_visible_fraction,
+            } => Self::KeepContentVisible {
+                min_visible_fraction,
+            },
+        }
+    }
+}
+
+/// See `PanBounds::is_valid`
+impl TryFrom<PanBoundsV1> for PanBounds {
+    type Error = InvalidViewErr;
+
+    fn try_from(pan_bounds: PanBoundsV1) -> Result<Self, Self::Error> {
+        let pan_bounds = match pan_bounds {
+            PanBoundsV1::Unbounded => Self::Unbounded,
+            PanBoundsV1::KeepContentVisible {
+                min_visible_fraction,
+            } => Self::KeepContentVisible {
+                min_visible_fraction,
+            },
+        };
+        if pan_bounds.is_valid() {
+            Ok(pan_bounds)
+        } else {
+            Err(InvalidViewErr(format!("{:?}", pan_bounds)))
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum PreserveAspectRatioV1 {
+    None,
+    Align(AlignmentV1, MeetOrSliceV1),
+}
+
+impl From<PreserveAspectRatio> for PreserveAspectRatioV1 {
+    fn from(preserve_aspect_ratio: PreserveAspectRatio) -> Self {
+        match preserve_aspect_ratio {
+            PreserveAspectRatio::None => Self::None,
+            PreserveAspectRatio::Align(alignment, meet_or_slice) => {
+                Self::Align(alignment.into(), meet_or_slice.into())
+            }
+        }
+    }
+}
+
+impl From<PreserveAspectRatioV1> for PreserveAspectRatio {
+    fn from(preserve_aspect_ratio: PreserveAspectRatioV1) -> Self {
+        match preserve_aspect_ratio {
+            PreserveAspectRatioV1::None => Self::None,
+            PreserveAspectRatioV1::Align(alignment, meet_or_slice) => {
+                Self::Align(alignment.into(), meet_or_slice.into())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct AlignmentV1 {
+    x: AxisAlignmentV1,
+    y: AxisAlignmentV1,
+}
+
+impl From<Alignment> for AlignmentV1 {
+    fn from(alignment: Alignment) -> Self {
+        Self {
+            x: alignment.x.into(),
+            y: alignment.y.into(),
+        }
+    }
+}
+
+impl From<AlignmentV1> for Alignment {
+    fn from(alignment: AlignmentV1) -> Self {
+        Alignment::new(alignment.x.into(), alignment.y.into())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum AxisAlignmentV1 {
+    Min,
+    Mid,
+    Max,
+}
+
+impl From<AxisAlignment> for AxisAlignmentV1 {
+    fn from(axis_alignment: AxisAlignment) -> Self {
+        match axis_alignment {
+            AxisAlignment::Min => Self::Min,
+            AxisAlignment::Mid => Self::Mid,
+            AxisAlignment::Max => Self::Max,
+        }
+    }
+}
+
+impl From<AxisAlignmentV1> for AxisAlignment {
+    fn from(axis_alignment: AxisAlignmentV1) -> Self {
+        match axis_alignment {
+            AxisAlignmentV1::Min => Self::Min,
+            AxisAlignmentV1::Mid => Self::Mid,
+            AxisAlignmentV1::Max => Self::Max,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum MeetOrSliceV1 {
+    Meet,
+    Slice,
+}
+
+impl From<MeetOrSlice> for MeetOrSliceV1 {
+    fn from(meet_or_slice: MeetOrSlice) -> Self {
+        match meet_or_slice {
+            MeetOrSlice::Meet => Self::Meet,
+            MeetOrSlice::Slice => Self::Slice,
+        }
+    }
+}
+
+impl From<MeetOrSliceV1> for MeetOrSlice {
+    fn from(meet_or_slice: MeetOrSliceV1) -> Self {
+        match meet_or_slice {
+            MeetOrSliceV1::Meet => Self::Meet,
+            MeetOrSliceV1::Slice => Self::Slice,
+        }
+    }
+}
+
+/// The entries `a` to `f` of an `AffineTransformMatrix`
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MatrixV1 {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+    f: f64,
+}
+
+impl From<AffineTransformMatrix> for MatrixV1 {
+    fn from(matrix: AffineTransformMatrix) -> Self {
+        Self {
+            a: matrix.a(),
+            b: matrix.b(),
+            c: matrix.c(),
+            d: matrix.d(),
+            e: matrix.e(),
+            f: matrix.f(),
+        }
+    }
+}
+
+/// A singular matrix is valid, it only cannot be inverted.
+impl TryFrom<MatrixV1> for AffineTransformMatrix {
+    type Error = InvalidViewErr;
+
+    fn try_from(matrix: MatrixV1) -> Result<Self, Self::Error> {
+        let MatrixV1 { a, b, c, d, e, f } = matrix;
+        if [a, b, c, d, e, f].iter().all(|entry| entry.is_finite()) {
+            Ok(AffineTransformMatrix::new(a, b, c, d, e, f))
+        } else {
+            Err(InvalidViewErr(format!("{:?} is not finite", matrix)))
+        }
+    }
+}
+
+/// Angles are in radians.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DecompositionV1 {
+    translate_x: f64,
+    translate_y: f64,
+    rotation: f64,
+    skew_x: f64,
+    scale_x: f64,
+    scale_y: f64,
+}
+
+impl From<Decomposition> for DecompositionV1 {
+    fn from(decomposition: Decomposition) -> Self {
+        Self {
+            translate_x: decomposition.translate_x,
+            translate_y: decomposition.translate_y,
+            rotation: decomposition.rotation,
+            skew_x: decomposition.skew_x,
+            scale_x: decomposition.scale_x,
+            scale_y: decomposition.scale_y,
+        }
+    }
+}
+
+impl TryFrom<DecompositionV1> for Decomposition {
+    type Error = InvalidViewErr;
+
+    fn try_from(decomposition: DecompositionV1) -> Result<Self, Self::Error> {
+        let DecompositionV1 {
+            translate_x,
+            translate_y,
+            rotation,
+            skew_x,
+            scale_x,
+            scale_y,
+        } = decomposition;
+        if [translate_x, translate_y, rotation, skew_x, scale_x, scale_y]
+            .iter()
+            .all(|part| part.is_finite())
+        {
+            Ok(Decomposition {
+                translate_x,
+                translate_y,
+                rotation,
+                skew_x,
+                scale_x,
+                scale_y,
+            })
+        } else {
+            Err(InvalidViewErr(format!("{:?} is not finite", decomposition)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::svg::{SvgPoint, SvgRect, SvgVec};
+    use crate::ViewBox;
+
+    use super::*;
+
+    const VIEW_BOX_V1: &str = r#"{"version":"1","view_box":{"top_left":{"x":-100.0,"y":-50.0},"dimensions":{"x":200.0,"y":100.0}},"content_box":{"top_left":{"x":-40.0,"y":-40.0},"dimensions":{"x":80.0,"y":80.0}},"limits":{"min_scale":0.25,"max_scale":4.0,"pan_bounds":{"KeepContentVisible":{"min_visible_fraction":0.5}}},"preserve_aspect_ratio":{"Align":[{"x":"Min","y":"Max"},"Slice"]}}"#;
+
+    fn view_box_v1() -> ViewBox {
+        let mut view_box = ViewBox::new(
+            SvgRect::new(SvgPoint::new(-100.0, -50.0), SvgVec::new(200.0, 100.0)),
+            SvgRect::new(SvgPoint::new(-40.0, -40.0), SvgVec::new(80.0, 80.0)),
+        );
+        view_box.set_limits(PanZoomLimits::new(
+            0.25,
+            4.0,
+            PanBounds::KeepContentVisible {
+                min_visible_fraction: 0.5,
+            },
+        ));
+        view_box.set_preserve_aspect_ratio(PreserveAspectRatio::Align(
+            Alignment::new(AxisAlignment::Min, AxisAlignment::Max),
+            MeetOrSlice::Slice,
+        ));
+        view_box
+    }
+
+    #[test]
+    fn view_box_v1_text() {
+        let view_box: ViewBox = serde_json::from_str(VIEW_BOX_V1).unwrap();
+        assert_eq!(view_box, view_box_v1());
+    }
+
+    #[test]
+    fn view_box_v2_text() {
+        let json = r#"{"version":"2","view_box":{"top_left":{"x":-100.0,"y":-50.0},"dimensions":{"x":200.0,"y":100.0}},"content_box":{"top_left":{"x":-40.0,"y":-40.0},"dimensions":{"x":80.0,"y":80.0}},"rotation":0.0,"limits":{"min_scale":0.25,"max_scale":4.0,"pan_bounds":{"KeepContentVisible":{"min_visible_fraction":0.5}}},"preserve_aspect_ratio":{"Align":[{"x":"Min","y":"Max"},"Slice"]}}"#;
+        assert_eq!(serde_json::to_string(&view_box_v1()).unwrap(), json);
+        let view_box: ViewBox = serde_json::from_str(json).unwrap();
+        assert_eq!(view_box, view_box_v1());
+
+        let rotated = json.replace(r#""rotation":0.0"#, r#""rotation":-0.75"#);
+        let view_box: ViewBox = serde_json::from_str(&rotated).unwrap();
+        assert_eq!(view_box.rotation(), -0.75);
+        assert_eq!(serde_json::to_string(&view_box).unwrap(), rotated);
+        let not_finite = json.replace(r#""rotation":0.0"#, r#""rotation":1e999"#);
+        assert!(serde_json::from_str::<ViewBox>(&not_finite).is_err());
+    }
+
+    #[test]
+    fn default_view_box_v2_text() {
+        let json = r#"{"version":"2","view_box":{"top_left":{"x":-100.0,"y":-100.0},"dimensions":{"x":200.0,"y":200.0}},"content_box":{"top_left":{"x":-100.0,"y":-100.0},"dimensions":{"x":200.0,"y":200.0}},"rotation":0.0,"limits":{"min_scale":0.001,"max_scale":1000.0,"pan_bounds":"Unbounded"},"preserve_aspect_ratio":{"Align":[{"x":"Mid","y":"Mid"},"Meet"]}}"#;
+        assert_eq!(serde_json::to_string(&ViewBox::default()).unwrap(), json);
+        let view_box: ViewBox = serde_json::from_str(json).unwrap();
+        assert_eq!(view_box, ViewBox::default());
+    }
+
+    #[test]
+    fn preserve_aspect_ratio_none_text() {
+        let mut view_box = ViewBox::default();
+        view_box.set_preserve_aspect_ratio(PreserveAspectRatio::None);
+        let json = serde_json::to_string(&view_box).unwrap();
+        assert!(
+            json.ends_with(r#""preserve_aspect_ratio":"None"}"#),
+            "{}",
+            json
+        );
+        assert_eq!(serde_json::from_str::<ViewBox>(&json).unwrap(), view_box);
+    }
+
+    #[test]
+    fn view_box_scale_is_clamped_to_the_limits() {
+        // a scale of 80 / 10 = 8 exceeds the maximum of 2
+        let json = VIEW_BOX_V1
+            .replace(
+                r#""dimensions":{"x":200.0,"y":100.0}"#,
+                r#""dimensions":{"x":20.0,"y":10.0}"#,
+            )
+            .replace(r#""max_scale":4.0"#, r#""max_scale":2.0"#);
+        let view_box: ViewBox = serde_json::from_str(&json).unwrap();
+        assert_relative_eq!(view_box.scale(), 2.0);
+    }
+
+    /// Replaces the first occurrence of `from` in `VIEW_BOX_V1` and returns the
+    /// error of deserializing it.
+    fn view_box_v1_err(from: &str, to: &str) -> String {
+        assert!(VIEW_BOX_V1.contains(from), "{}", from);
+        let json = VIEW_BOX_V1.replacen(from, to, 1);
+        serde_json::from_str::<ViewBox>(&json)
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn invalid_view_boxes_are_rejected() {
+        for (from, to) in [
+            // a zero-size view box
+            (
+                r#""dimensions":{"x":200.0,"y":100.0}"#,
+                r#""dimensions":{"x":0.0,"y":100.0}"#,
+            ),
+            // a negative size
+            (
+                r#""dimensions":{"x":80.0,"y":80.0}"#,
+                r#""dimensions":{"x":80.0,"y":-80.0}"#,
+            ),
+            // a negative minimum scale
+            (r#""min_scale":0.25"#, r#""min_scale":-0.25"#),
+            // a minimum scale of zero
+            (r#""min_scale":0.25"#, r#""min_scale":0.0"#),
+            // a minimum scale above the maximum
+            (r#""min_scale":0.25"#, r#""min_scale":8.0"#),
+            // a visible fraction above 1
+            (
+                r#""min_visible_fraction":0.5"#,
+                r#""min_visible_fraction":1.5"#,
+            ),
+        ] {
+            let err = view_box_v1_err(from, to);
+            assert!(err.starts_with("Invalid view: "), "{}: {}", to, err);
+        }
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        assert!(view_box_v1_err(r#""version":"1""#, r#""version":"0""#).contains("unknown variant"));
+    }
+
+    #[test]
+    fn limits_v1_text() {
+        let limits = LimitsV1::from(PanZoomLimits::default());
+        let json = serde_json::to_string(&limits).unwrap();
+        assert_eq!(
+            json,
+            r#"{"min_scale":0.001,"max_scale":1000.0,"pan_bounds":"Unbounded"}"#
+        );
+        assert_eq!(serde_json::from_str::<LimitsV1>(&json).unwrap(), limits);
+    }
+
+    #[test]
+    fn geometry_v1_text() {
+        let rect = SvgRect::new(SvgPoint::new(1.5, -2.0), SvgVec::new(3.0, 4.0));
+        let json = r#"{"top_left":{"x":1.5,"y":-2.0},"dimensions":{"x":3.0,"y":4.0}}"#;
+        assert_eq!(serde_json::to_string(&rect).unwrap(), json);
+        assert_eq!(serde_json::from_str::<SvgRect>(json).unwrap(), rect);
+        assert_eq!(
+            serde_json::to_string(&PanZoomLimits::default()).unwrap(),
+            r#"{"min_scale":0.001,"max_scale":1000.0,"pan_bounds":"Unbounded"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&PreserveAspectRatio::default()).unwrap(),
+            r#"{"Align":[{"x":"Mid","y":"Mid"},"Meet"]}"#
+        );
+        let matrix = AffineTransformMatrix::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0);
+        let json = r#"{"a":1.0,"b":2.0,"c":3.0,"d":4.0,"e":5.0,"f":6.0}"#;
+        assert_eq!(serde_json::to_string(&matrix).unwrap(), json);
+        assert_eq!(
+            serde_json::from_str::<AffineTransformMatrix>(json).unwrap(),
+            matrix
+        );
+        let decomposition = AffineTransformMatrix::translation(5.0, 6.0)
+            .try_decompose()
+            .unwrap();
+        let json = r#"{"translate_x":5.0,"translate_y":6.0,"rotation":0.0,"skew_x":0.0,"scale_x":1.0,"scale_y":1.0}"#;
+        assert_eq!(serde_json::to_string(&decomposition).unwrap(), json);
+        assert_eq!(
+            serde_json::from_str::<Decomposition>(json).unwrap(),
+            decomposition
+        );
+    }
+
+    #[test]
+    fn invalid_geometry_is_rejected() {
+        assert!(serde_json::from_str::<SvgPoint>(r#"{"x":1e999,"y":0.0}"#).is_err());
+        assert!(serde_json::from_str::<SvgRect>(
+            r#"{"top_left":{"x":0.0,"y":0.0},"dimensions":{"x":-1.0,"y":1.0}}"#
+        )
+        .unwrap_err()
+        .to_string()
+        .starts_with("Invalid view: "));
+        assert!(serde_json::from_str::<PanBounds>(
+            r#"{"KeepContentVisible":{"min_visible_fraction":1.5}}"#
+        )
+        .is_err());
+        assert!(serde_json::from_str::<PanZoomLimits>(
+            r#"{"min_scale":2.0,"max_scale":1.0,"pan_bounds":"Unbounded"}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn validate_scale_and_rotation() {
+        assert_eq!(validate_scale(2.0).unwrap(), 2.0);
+        assert!(validate_scale(0.0).is_err());
+        assert!(validate_scale(-1.0).is_err());
+        assert!(validate_scale(f64::INFINITY).is_err());
+        assert_eq!(validate_rotation(-1.5).unwrap(), -1.5);
+        assert!(validate_rotation(f64::NAN).is_err());
+    }
+
+    /// The states of HTML and SVG elements
+    mod element_states {
+        use crate::{ElementTransform, ViewState};
+
+        const VIEW_STATE_V1: &str = r#"{"version":"1","top_left":{"x":0.0,"y":0.0},"scale":1.0,"rotation":0.0,"limits":{"min_scale":0.001,"max_scale":1000.0,"pan_bounds":"Unbounded"}}"#;
+        const ELEMENT_TRANSFORM_V1: &str = r#"{"version":"1","translate":{"x":0.0,"y":0.0},"scale":1.0,"rotation":0.0,"limits":{"min_scale":0.001,"max_scale":1000.0,"pan_bounds":"Unbounded"}}"#;
+
+        #[test]
+        fn view_state_v1_text() {
+            assert_eq!(
+                serde_json::to_string(&ViewState::new()).unwrap(),
+                VIEW_STATE_V1
+            );
+            let view_state: ViewState = serde_json::from_str(VIEW_STATE_V1).unwrap();
+            assert_eq!(view_state, ViewState::new());
+            let json = r#"{"version":"1","top_left":{"x":12.5,"y":-3.0},"scale":2.5,"rotation":-0.75,"limits":{"min_scale":0.5,"max_scale":8.0,"pan_bounds":{"KeepContentVisible":{"min_visible_fraction":1.0}}}}"#;
+            let view_state: ViewState = serde_json::from_str(json).unwrap();
+            assert_eq!(serde_json::to_string(&view_state).unwrap(), json);
+        }
+
+        #[test]
+        fn element_transform_v1_text() {
+            assert_eq!(
+                serde_json::to_string(&ElementTransform::new()).unwrap(),
+                ELEMENT_TRANSFORM_V1
+            );
+            let element_transform: ElementTransform =
+                serde_json::from_str(ELEMENT_TRANSFORM_V1).unwrap();
+            assert_eq!(element_transform, ElementTransform::new());
+            let json = r#"{"version":"1","translate":{"x":-7.0,"y":4.25},"scale":0.5,"rotation":3.0,"limits":{"min_scale":0.5,"max_scale":8.0,"pan_bounds":"Unbounded"}}"#;
+            let element_transform: ElementTransform = serde_json::from_str(json).unwrap();
+            assert_eq!(serde_json::to_string(&element_transform).unwrap(), json);
+        }
+
+        #[test]
+        fn scales_are_clamped_to_the_limits() {
+            let view_state: ViewState =
+                serde_json::from_str(&VIEW_STATE_V1.replace(r#""scale":1.0"#, r#""scale":5000.0"#))
+                    .unwrap();
+            assert_eq!(
+                serde_json::to_string(&view_state).unwrap(),
+                VIEW_STATE_V1.replace(r#""scale":1.0"#, r#""scale":1000.0"#)
+            );
+            let element_transform: ElementTransform = serde_json::from_str(
+                &ELEMENT_TRANSFORM_V1.replace(r#""scale":1.0"#, r#""scale":0.0001"#),
+            )
+            .unwrap();
+            assert_eq!(
+                serde_json::to_string(&element_transform).unwrap(),
+                ELEMENT_TRANSFORM_V1.replace(r#""scale":1.0"#, r#""scale":0.001"#)
+            );
+        }
+
+        #[test]
+        fn invalid_states_are_rejected() {
+            for (from, to) in [
+                (r#""scale":1.0"#, r#""scale":0.0"#),
+                (r#""scale":1.0"#, r#""scale":-1.0"#),
+                (r#""min_scale":0.001"#, r#""min_scale":-0.001"#),
+                (r#""min_scale":0.001"#, r#""min_scale":2000.0"#),
+            ] {
+                let err = serde_json::from_str::<ViewState>(&VIEW_STATE_V1.replace(from, to))
+                    .unwrap_err()
+                    .to_string();
+                assert!(err.starts_with("Invalid view: "), "{}: {}", to, err);
+                let err = serde_json::from_str::<ElementTransform>(
+                    &ELEMENT_TRANSFORM_V1.replace(from, to),
+                )
+                .unwrap_err()
+                .to_string();
+                assert!(err.starts_with("Invalid view: "), "{}: {}", to, err);
+            }
+        }
+    }
+}
@@ -1,206 +1,185 @@
 use std::fmt::{Display, Formatter};
-use std::ops::{Add, Div, Mul, Sub};
 
 use approx::abs_diff_eq;
 use num_traits::Zero;
 
+use crate::geom::{Point, Rect, Svg, Transform, Vector};
+use crate::{AffineTransformMatrix, PanZoomLimits};
+
 pub trait ToSvgString {
     fn to_svg_string(&self) -> String;
 }
 
 /// A point in the SVG coordinate system
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub struct SvgPoint {
-    pub x: f64,
-    pub y: f64,
-}
+pub type SvgPoint = Point<Svg>;
 
-impl Display for SvgPoint {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "SvgPoint ({}, {})", self.x, self.y)
-    }
-}
-impl SvgPoint {
-    pub fn new(x: f64, y: f64) -> Self {
-        Self { x, y }
-    }
-}
+/// An element of the vector space of the SVG coordinate system
+pub type SvgVec = Vector<Svg>;
 
-impl Add<SvgVec> for SvgPoint {
-    type Output = SvgPoint;
+/// A rect in the SVG coordinate system
+pub type SvgRect = Rect<Svg>;
 
-    fn add(self, rhs: SvgVec) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
-    }
+/// How the `ViewBox` is fitted into the view port, if their aspect ratios
+/// differ.
+///
+/// # See
+/// https://developer.mozilla.org/en-US/docs/Web/SVG/Attribute/preserveAspectRatio
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialization::PreserveAspectRatioV1",
+        from = "crate::serialization::PreserveAspectRatioV1"
+    )
+)]
+pub enum PreserveAspectRatio {
+    /// Scales non-uniformly, such that the view box fills the view port
+    /// exactly.
+    None,
+    /// Scales uniformly and aligns the view box within the view port.
+    Align(Alignment, MeetOrSlice),
 }
 
-impl Sub for SvgPoint {
-    type Output = SvgVec;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        SvgVec::new(self.x - rhs.x, self.y - rhs.y)
+impl Default for PreserveAspectRatio {
+    /// The browser's default, `"xMidYMid meet"`
+    fn default() -> Self {
+        Self::Align(
+            Alignment::new(AxisAlignment::Mid, AxisAlignment::Mid),
+            MeetOrSlice::Meet,
+        )
     }
 }
 
-impl Sub<SvgVec> for SvgPoint {
-    type Output = SvgPoint;
-
-    fn sub(self, rhs: SvgVec) -> Self::Output {
-        Self {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
+impl ToSvgString for PreserveAspectRatio {
+    fn to_svg_string(&self) -> String {
+        match self {
+            PreserveAspectRatio::None => "none".to_string(),
+            PreserveAspectRatio::Align(alignment, meet_or_slice) => format!(
+                "x{}Y{} {}",
+                alignment.x.to_svg_string(),
+                alignment.y.to_svg_string(),
+                meet_or_slice.to_svg_string()
+            ),
         }
     }
 }
 
-impl Mul<f64> for SvgPoint {
-    type Output = Self;
-
-    fn mul(self, rhs: f64) -> Self::Output {
-        Self {
-            x: self.x * rhs,
-            y: self.y * rhs,
-        }
+impl PreserveAspectRatio {
+    /// Returns the part of `view_box` that is visible in a view port with
+    /// `view_port_aspect_ratio` (width / height).
+    ///
+    /// It is larger than `view_box` for `Meet` and smaller for `Slice`, if the
+    /// aspect ratios differ.
+    pub fn visible_rect(&self, view_box: SvgRect, view_port_aspect_ratio: f64) -> SvgRect {
+        let (alignment, meet_or_slice) = match self {
+            PreserveAspectRatio::None => return view_box,
+            PreserveAspectRatio::Align(alignment, meet_or_slice) => (alignment, meet_or_slice),
+        };
+        let view_box_is_wider = view_box.aspect_ratio() > view_port_aspect_ratio;
+        // whether the width of the view box determines the scale
+        let fit_width = match meet_or_slice {
+            MeetOrSlice::Meet => view_box_is_wider,
+            MeetOrSlice::Slice => !view_box_is_wider,
+        };
+        let dimensions = if fit_width {
+            SvgVec::new(view_box.width(), view_box.width() / view_port_aspect_ratio)
+        } else {
+            SvgVec::new(
+                view_box.height() * view_port_aspect_ratio,
+                view_box.height(),
+            )
+        };
+        let surplus = dimensions - view_box.dimensions();
+        let top_left = SvgPoint::new(
+            view_box.left() - surplus.x * alignment.x.fraction(),
+            view_box.top() - surplus.y * alignment.y.fraction(),
+        );
+        SvgRect::new(top_left, dimensions)
     }
 }
 
-/// An element of the vector space of the SVG coordinate system
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub struct SvgVec {
-    x: f64,
-    y: f64,
+/// The alignment part of `preserveAspectRatio`, e.g. `xMidYMid`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialization::AlignmentV1",
+        from = "crate::serialization::AlignmentV1"
+    )
+)]
+pub struct Alignment {
+    pub x: AxisAlignment,
+    pub y: AxisAlignment,
 }
 
-impl Add for SvgVec {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
+impl Alignment {
+    pub fn new(x: AxisAlignment, y: AxisAlignment) -> Self {
+        Self { x, y }
     }
 }
-impl Sub for SvgVec {
-    type Output = Self;
 
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
-        }
-    }
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialization::AxisAlignmentV1",
+        from = "crate::serialization::AxisAlignmentV1"
+    )
+)]
+pub enum AxisAlignment {
+    Min,
+    Mid,
+    Max,
 }
 
-impl Mul<f64> for SvgVec {
-    type Output = Self;
-
-    fn mul(self, rhs: f64) -> Self::Output {
-        Self {
-            x: self.x * rhs,
-            y: self.y * rhs,
+impl ToSvgString for AxisAlignment {
+    fn to_svg_string(&self) -> String {
+        match self {
+            AxisAlignment::Min => "Min",
+            AxisAlignment::Mid => "Mid",
+            AxisAlignment::Max => "Max",
         }
+        .to_string()
     }
 }
 
-impl Div<f64> for SvgVec {
-    type Output = Self;
-
-    fn div(self, rhs: f64) -> Self::Output {
-        Self {
-            x: self.x / rhs,
-            y: self.y / rhs,
+impl AxisAlignment {
+    /// The fraction of the surplus space before the view box
+    fn fraction(&self) -> f64 {
+        match self {
+            AxisAlignment::Min => 0.0,
+            AxisAlignment::Mid => 0.5,
+            AxisAlignment::Max => 1.0,
         }
     }
 }
 
-impl Zero for SvgVec {
-    fn zero() -> Self {
-        Self { x: 0.0, y: 0.0 }
-    }
-
-    fn is_zero(&self) -> bool {
-        self.x.is_zero() && self.y.is_zero()
-    }
-}
-impl SvgVec {
-    pub fn new(x: f64, y: f64) -> Self {
-        Self { x, y }
-    }
-
-    pub fn x(&self) -> f64 {
-        self.x
-    }
-
-    pub fn y(&self) -> f64 {
-        self.y
-    }
-}
-
-// unlike the mathematical `BoundingRect`, SvgRect's top<bottom
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub struct SvgRect {
-    pub top_left: SvgPoint,
-    pub dimensions: SvgVec,
-}
-
-impl Display for SvgRect {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "SvgRect x:{{{}..{}}} ⨯ y:{{{}..{}}}",
-            self.left(),
-            self.right(),
-            self.top(),
-            self.bottom()
-        )
-    }
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialization::MeetOrSliceV1",
+        from = "crate::serialization::MeetOrSliceV1"
+    )
+)]
+pub enum MeetOrSlice {
+    /// The whole view box is visible, the view port may show more.
+    Meet,
+    /// The view box fills the whole view port, parts of it may be cut off.
+    Slice,
 }
 
-impl SvgRect {
-    pub fn new(top_left: SvgPoint, dimensions: SvgVec) -> Self {
-        debug_assert!(dimensions.x >= 0.0);
-        debug_assert!(dimensions.y >= 0.0);
-        Self {
-            top_left,
-            dimensions,
+impl ToSvgString for MeetOrSlice {
+    fn to_svg_string(&self) -> String {
+        match self {
+            MeetOrSlice::Meet => "meet",
+            MeetOrSlice::Slice => "slice",
         }
-    }
-    pub fn top(&self) -> f64 {
-        self.top_left.y
-    }
-    pub fn bottom(&self) -> f64 {
-        self.top() + self.height()
-    }
-    pub fn left(&self) -> f64 {
-        self.top_left.x
-    }
-    pub fn right(&self) -> f64 {
-        self.left() + self.width()
-    }
-    pub fn width(&self) -> f64 {
-        self.dimensions.x
-    }
-    pub fn height(&self) -> f64 {
-        self.dimensions.y
-    }
-    pub fn top_left(&self) -> SvgPoint {
-        self.top_left
-    }
-    pub fn bottom_right(&self) -> SvgPoint {
-        SvgPoint::new(self.right(), self.bottom())
-    }
-
-    pub fn dimensions(&self) -> SvgVec {
-        self.dimensions
-    }
-
-    /// width / height
-    pub fn aspect_radio(&self) -> f64 {
-        self.width() / self.height()
+        .to_string()
     }
 }
 
@@ -213,31 +192,65 @@ impl SvgRect {
 /// `ViewBox` is not aware of padding. To create padding just create a larger
 /// view box.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialization::ViewBoxRepr",
+        try_from = "crate::serialization::ViewBoxRepr"
+    )
+)]
 pub struct ViewBox {
     /// The visible part of the SVG. May contain parts or all of the content or
     /// a much wider area than the content.
     ///
     /// Its size and position are controlled by the user; its shape (i.e. aspect
     /// ratio) is controlled by by changes to the view-port.
-    // FIXME: Its aspect ratio must must match the view-port's (not the content's ) to avoid empty
-    // borders, when zooming in -> There must be a setter for the aspect ratio
+    // Its aspect ratio must match the view-port's (not the content's) to avoid empty
+    // borders, when zooming in -> `set_aspect_ratio`, e.g. by `sync_view_box_with_view_port`
     view_box: SvgRect,
     /// The bounding box of content in the SVG canvas. Changes when the content
     /// changes.
     // Used as a measure for determining the scale of the ViewBox.
     content_box: SvgRect,
+    /// The rotation of the content around the center of the content box, in
+    /// radians, clockwise on screen. The view box stays axis-aligned in the
+    /// canvas; the content is rotated within it by a `<g>` wrapping it, see
+    /// `content_transform`.
+    rotation: f64,
+    /// Enforced by every change of scale or position.
+    limits: PanZoomLimits,
+    preserve_aspect_ratio: PreserveAspectRatio,
+    /// The aspect ratio of the view port, once known. Together with
+    /// `preserve_aspect_ratio` it determines the part of the canvas, that is
+    /// actually visible -> `visible_rect`.
+    // Not persisted, it belongs to the page showing the view box.
+    view_port_aspect_ratio: Option<f64>,
+}
+
+/// View boxes are equal, if they show the same view. The recorded aspect ratio
+/// of the view port is ignored, as it only follows the page.
+impl PartialEq for ViewBox {
+    fn eq(&self, other: &Self) -> bool {
+        self.view_box == other.view_box
+            && self.content_box == other.content_box
+            && self.rotation == other.rotation
+            && self.limits == other.limits
+            && self.preserve_aspect_ratio == other.preserve_aspect_ratio
+    }
 }
 
 impl Default for ViewBox {
     fn default() -> Self {
-        let rect = SvgRect {
-            top_left: SvgPoint::new(-100.0, -100.0),
-            dimensions: SvgVec::new(200.0, 200.0),
-        };
+        let rect = SvgRect::new(SvgPoint::new(-100.0, -100.0), SvgVec::new(200.0, 200.0));
         Self {
             view_box: rect,
 
             content_box: rect,
+            rotation: 0.0,
+            limits: PanZoomLimits::default(),
+            preserve_aspect_ratio: PreserveAspectRatio::default(),
+            view_port_aspect_ratio: None,
         }
     }
 }
@@ -247,6 +260,10 @@ impl ViewBox {
         Self {
             view_box,
             content_box,
+            rotation: 0.0,
+            limits: PanZoomLimits::default(),
+            preserve_aspect_ratio: PreserveAspectRatio::default(),
+            view_port_aspect_ratio: None,
         }
     }
 
@@ -269,7 +286,7 @@ impl ViewBox {
         } else if self.content_box.width() == 0.0 {
             self.content_box.height() / self.height()
         } else if self.content_box.height() == 0.0 {
-            self.content_box.height() / self.width()
+            self.content_box.width() / self.width()
         } else {
             f64::max(
                 self.content_box.width() / self.width(),
@@ -277,14 +294,16 @@ impl ViewBox {
             )
         }
     }
+    /// `new_scale` is clamped to the limits.
     pub fn set_scale(&mut self, new_scale: f64) {
+        let new_scale = self.limits.clamp_scale(new_scale);
         info!(
             "Changing scale of view box from {} to {}",
             self.scale(),
             new_scale
         );
-        debug_assert!(new_scale >= 0.0);
-        self.view_box.dimensions = self.content_box.dimensions / new_scale;
+        debug_assert!(new_scale > 0.0);
+        self.view_box.dimensions = self.dimensions_for(new_scale, self.view_box.aspect_ratio());
 
         debug_assert!(
             abs_diff_eq!(self.scale(), new_scale, epsilon = 1e-12),
@@ -293,40 +312,407 @@ impl ViewBox {
             new_scale
         );
     }
+    /// Changes the shape of the view box to `aspect_ratio` (width / height),
+    /// keeping its center and scale. Must be called, whenever the aspect ratio
+    /// of the view port changes.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f64) {
+        info!(
+            "Changing aspect ratio of view box from {} to {}",
+            self.view_box.aspect_ratio(),
+            aspect_ratio
+        );
+        let center = self.view_box.center();
+        self.view_box.dimensions = self.dimensions_for(self.scale(), aspect_ratio);
+        self.view_port_aspect_ratio = Some(aspect_ratio).filter(|ar| ar.is_finite() && *ar > 0.0);
+        self.set_top_left(center - self.view_box.dimensions / 2.0);
+    }
+    /// Records the aspect ratio (width / height) of the view port, without
+    /// changing the shape of the view box, and brings the visible part within
+    /// the pan bounds.
+    pub fn set_view_port_aspect_ratio(&mut self, aspect_ratio: f64) {
+        let aspect_ratio = Some(aspect_ratio).filter(|ar| ar.is_finite() && *ar > 0.0);
+        if aspect_ratio != self.view_port_aspect_ratio {
+            self.view_port_aspect_ratio = aspect_ratio;
+            self.set_top_left(self.top_left());
+        }
+    }
+    /// Returns the part of the canvas, that is actually visible in the view
+    /// port. It differs from the view box, if their aspect ratios differ --
+    /// then `preserve_aspect_ratio` decides. Without a known view port, it is
+    /// the view box.
+    pub fn visible_rect(&self) -> SvgRect {
+        match self.view_port_aspect_ratio {
+            Some(aspect_ratio) => self
+                .preserve_aspect_ratio
+                .visible_rect(self.view_box, aspect_ratio),
+            None => self.view_box,
+        }
+    }
+    /// Returns the dimensions of a view box with `scale` and `aspect_ratio`.
+    ///
+    /// Falls back to the aspect ratio of the content box, if either is
+    /// degenerate.
+    fn dimensions_for(&self, scale: f64, aspect_ratio: f64) -> SvgVec {
+        let content_width = self.content_box.width();
+        let content_height = self.content_box.height();
+        if content_width == 0.0
+            || content_height == 0.0
+            || !aspect_ratio.is_finite()
+            || aspect_ratio <= 0.0
+        {
+            return self.content_box.dimensions / scale;
+        }
+        // scale = max(content_width / width, content_height / height)
+        let width = f64::max(content_width, content_height * aspect_ratio) / scale;
+        SvgVec::new(width, width / aspect_ratio)
+    }
     pub fn top_left(&self) -> SvgPoint {
         self.view_box.top_left
     }
 
+    /// `pos` is clamped to the pan bounds of the limits.
     pub fn set_top_left(&mut self, pos: SvgPoint) {
+        let pos = self.clamp_top_left(pos);
         info!(
             "Changing top left of view box from {} to {}",
             self.view_box.top_left, pos
         );
         self.view_box.top_left = pos;
     }
+    /// Returns the position closest to `pos`, that the top left corner of
+    /// the view box may have with the current scale.
+    ///
+    /// The pan bounds apply to the visible part of the canvas, see
+    /// `visible_rect`, and the bounds of the rotated content, see
+    /// `content_bounds`.
+    pub fn clamp_top_left(&self, pos: SvgPoint) -> SvgPoint {
+        let visible = self.visible_rect();
+        let visible_offset = visible.top_left() - self.view_box.top_left;
+        let visible_pos = pos + visible_offset;
+        let content = self.content_bounds();
+        SvgPoint::new(
+            self.limits.clamp_pan(
+                visible_pos.x,
+                visible.width(),
+                content.left(),
+                content.width(),
+            ),
+            self.limits.clamp_pan(
+                visible_pos.y,
+                visible.height(),
+                content.top(),
+                content.height(),
+            ),
+        ) - visible_offset
+    }
+    /// Pans, such that `point` is at the center of the visible part of the
+    /// canvas, see `visible_rect`.
+    pub fn center_visible_at(&mut self, point: SvgPoint) {
+        let visible_center_offset = self.visible_rect().center() - self.view_box.top_left;
+        self.set_top_left(point - visible_center_offset);
+    }
+    pub fn limits(&self) -> PanZoomLimits {
+        self.limits
+    }
+    /// Changes the limits and brings scale and position within them.
+    pub fn set_limits(&mut self, limits: PanZoomLimits) {
+        self.limits = limits;
+        let scale = self.scale();
+        if self.limits.clamp_scale(scale) != scale {
+            self.set_scale(scale);
+        }
+        self.set_top_left(self.top_left());
+    }
     pub fn content_box(&self) -> SvgRect {
         self.content_box
     }
+    /// In radians, see `content_transform`
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+    /// Rotates the content to `rotation` radians around the center of the
+    /// content box. The view box keeps its position, unless the pan bounds
+    /// move it.
+    pub fn set_rotation(&mut self, rotation: f64) {
+        self.rotation = rotation;
+        self.set_top_left(self.top_left());
+    }
+    /// Rotates the content to `rotation` radians and pans, such that the
+    /// content shown at `fix_point` of the canvas stays there -- unless the
+    /// pan bounds prevent it.
+    pub fn set_rotation_around(&mut self, rotation: f64, fix_point: SvgPoint) {
+        let moved_fix_point =
+            rotation_around(self.content_box.center(), rotation - self.rotation).apply(fix_point);
+        self.rotation = rotation;
+        self.set_top_left(self.top_left() + (moved_fix_point - fix_point));
+    }
+    /// Maps the user space of the content -- i.e. of the `<g>` wrapping it --
+    /// to the canvas of the `<svg>` element, which the view box refers to.
+    pub fn content_to_canvas(&self) -> Transform<Svg, Svg> {
+        rotation_around(self.content_box.center(), self.rotation)
+    }
+    /// The inverse of `content_to_canvas`
+    pub fn canvas_to_content(&self) -> Transform<Svg, Svg> {
+        rotation_around(self.content_box.center(), -self.rotation)
+    }
+    /// The `transform` attribute of the `<g>` wrapping the content:
+    /// `rotate(rotation cx cy)` in degrees around the center of the content
+    /// box.
+    pub fn content_transform(&self) -> String {
+        let center = self.content_box.center();
+        format!(
+            "rotate({} {} {})",
+            self.rotation.to_degrees(),
+            center.x,
+            center.y
+        )
+    }
+    /// The bounding rect of the rotated content in the canvas, which the pan
+    /// bounds refer to. It is the content box, unless the content is rotated.
+    pub fn content_bounds(&self) -> SvgRect {
+        if self.rotation == 0.0 {
+            self.content_box
+        } else {
+            self.content_to_canvas().apply_to_rect(self.content_box)
+        }
+    }
+    /// Changes the content box and brings scale and position within the
+    /// limits, which refer to the content box.
     pub fn set_content_box(&mut self, rect: SvgRect) {
-        self.content_box = rect
+        self.content_box = rect;
+        let scale = self.scale();
+        if self.limits.clamp_scale(scale) != scale {
+            self.set_scale(scale);
+        }
+        self.set_top_left(self.top_left());
     }
     pub fn view_box(&self) -> SvgRect {
         self.view_box
     }
+    pub fn preserve_aspect_ratio(&self) -> PreserveAspectRatio {
+        self.preserve_aspect_ratio
+    }
+    pub fn set_preserve_aspect_ratio(&mut self, preserve_aspect_ratio: PreserveAspectRatio) {
+        self.preserve_aspect_ratio = preserve_aspect_ratio
+    }
+
+    /// Pans and zooms, such that `rect` of the canvas -- i.e. rotated with the
+    /// content, see `content_to_canvas` -- is visible, as large as possible
+    /// and centered.
+    ///
+    /// The view box keeps its aspect ratio, which should be the view port's.
+    /// If the aspect ratio of `rect` differs, the view box extends `rect` in
+    /// one dimension -- as the browser does with `preserveAspectRatio="xMidYMid
+    /// meet"`. If the view box is not in sync with the view port, the visible
+    /// part of the canvas is fitted instead, see `visible_rect`.
+    pub fn zoom_to_rect(&mut self, rect: SvgRect) {
+        let view_box_to_rect_ratio =
+            f64::max(rect.width() / self.width(), rect.height() / self.height());
+        info!("Zooming {} to {}", self, rect);
+        self.set_scale(self.scale() / view_box_to_rect_ratio);
+        self.center_visible_at(rect.center());
+        let visible = self.visible_rect();
+        if !visible.contains_rect(&rect) {
+            // `slice` cuts off a part of the view box
+            let visible_to_rect_ratio = f64::max(
+                rect.width() / visible.width(),
+                rect.height() / visible.height(),
+            );
+            self.set_scale(self.scale() / visible_to_rect_ratio);
+            self.center_visible_at(rect.center());
+        }
+    }
+
+    /// Pans and zooms, such that the whole -- possibly rotated -- content box
+    /// and `padding` SVG units around it are visible, as large as possible and
+    /// centered.
+    pub fn fit_content(&mut self, padding: f64) {
+        self.zoom_to_rect(self.content_bounds().inflate(SvgVec::new(padding, padding)));
+    }
 }
+
+/// Rotates by `angle` radians around `center`.
+fn rotation_around(center: SvgPoint, angle: f64) -> Transform<Svg, Svg> {
+    if angle == 0.0 {
+        return Transform::identity();
+    }
+    Transform::new(
+        AffineTransformMatrix::translation(center.x, center.y)
+            * AffineTransformMatrix::rotation(angle)
+            * AffineTransformMatrix::translation(-center.x, -center.y),
+    )
+}
+#[cfg(feature = "serde")]
+impl From<ViewBox> for crate::serialization::ViewBoxRepr {
+    fn from(view_box: ViewBox) -> Self {
+        Self::V2 {
+            view_box: view_box.view_box.into(),
+            content_box: view_box.content_box.into(),
+            rotation: view_box.rotation,
+            limits: view_box.limits.into(),
+            preserve_aspect_ratio: view_box.preserve_aspect_ratio.into(),
+        }
+    }
+}
+
+/// Rejects an empty view box, a rotation, that is not finite, and invalid
+/// limits. The scale and position are brought within the limits. Version 1
+/// has no rotation.
+#[cfg(feature = "serde")]
+impl TryFrom<crate::serialization::ViewBoxRepr> for ViewBox {
+    type Error = crate::serialization::InvalidViewErr;
+
+    fn try_from(repr: crate::serialization::ViewBoxRepr) -> Result<Self, Self::Error> {
+        let (view_box, content_box, rotation, limits, preserve_aspect_ratio) = match repr {
+            crate::serialization::ViewBoxRepr::V1 {
+                view_box,
+                content_box,
+                limits,
+                preserve_aspect_ratio,
+            } => (view_box, content_box, 0.0, limits, preserve_aspect_ratio),
+            crate::serialization::ViewBoxRepr::V2 {
+                view_box,
+                content_box,
+                rotation,
+                limits,
+                preserve_aspect_ratio,
+            } => (
+                view_box,
+                content_box,
+                rotation,
+                limits,
+                preserve_aspect_ratio,
+            ),
+        };
+        let view_box: SvgRect = view_box.try_into()?;
+        if view_box.is_empty() {
+            return Err(crate::serialization::InvalidViewErr::new(format!(
+                "view box {} is empty",
+                view_box.to_svg_string()
+            )));
+        }
+        let mut restored = Self::new(view_box, content_box.try_into()?);
+        restored.rotation = crate::serialization::validate_rotation(rotation)?;
+        restored.set_preserve_aspect_ratio(preserve_aspect_ratio.into());
+        restored.set_limits(limits.try_into()?);
+        Ok(restored)
+    }
+}
+
 impl Display for ViewBox {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "ViewBox {{{}}}", self.to_svg_string())
     }
 }
 impl ToSvgString for ViewBox {
+    fn to_svg_string(&self) -> String {
+        self.view_box.to_svg_string()
+    }
+}
+
+/// In the format of the `viewBox` attribute: `min-x min-y width height`
+impl ToSvgString for SvgRect {
     fn to_svg_string(&self) -> String {
         format!(
             "{} {} {} {}",
-            self.min_x(),
-            self.min_y(),
+            self.left(),
+            self.top(),
             self.width(),
             self.height()
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(left: f64, top: f64, width: f64, height: f64) -> SvgRect {
+        SvgRect::new(SvgPoint::new(left, top), SvgVec::new(width, height))
+    }
+
+    #[test]
+    fn scale_is_the_larger_ratio_of_content_to_view_box() {
+        let content_box = rect(0.0, 0.0, 100.0, 50.0);
+        assert_eq!(ViewBox::new(content_box, content_box).scale(), 1.0);
+        assert_eq!(
+            ViewBox::new(rect(0.0, 0.0, 50.0, 50.0), content_box).scale(),
+            2.0
+        );
+        assert_eq!(
+            ViewBox::new(rect(0.0, 0.0, 200.0, 25.0), content_box).scale(),
+            2.0
+        );
+    }
+
+    #[test]
+    fn scale_of_a_point_content_box_is_one() {
+        let content_box = rect(5.0, 5.0, 0.0, 0.0);
+        assert_eq!(
+            ViewBox::new(rect(0.0, 0.0, 10.0, 10.0), content_box).scale(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn scale_of_a_line_content_box_ignores_its_empty_dimension() {
+        // a vertical line
+        let content_box = rect(0.0, 0.0, 0.0, 100.0);
+        assert_eq!(
+            ViewBox::new(rect(0.0, 0.0, 400.0, 50.0), content_box).scale(),
+            2.0
+        );
+        // a horizontal line
+        let content_box = rect(0.0, 0.0, 100.0, 0.0);
+        assert_eq!(
+            ViewBox::new(rect(0.0, 0.0, 25.0, 400.0), content_box).scale(),
+            4.0
+        );
+    }
+
+    #[test]
+    fn set_scale_keeps_the_aspect_ratio() {
+        let mut view_box = ViewBox::new(rect(0.0, 0.0, 200.0, 100.0), rect(0.0, 0.0, 50.0, 50.0));
+        view_box.set_scale(0.5);
+        assert_relative_eq!(view_box.scale(), 0.5);
+        assert_relative_eq!(view_box.view_box().aspect_ratio(), 2.0);
+    }
+
+    #[test]
+    fn equality_ignores_the_view_port() {
+        let view_box = ViewBox::new(rect(0.0, 0.0, 50.0, 50.0), rect(0.0, 0.0, 100.0, 100.0));
+        let mut recorded = view_box;
+        recorded.set_view_port_aspect_ratio(1.0);
+        assert_eq!(recorded, view_box);
+        recorded.set_scale(4.0);
+        assert_ne!(recorded, view_box);
+    }
+
+    #[test]
+    fn rotating_around_a_fix_point_keeps_its_content_there() {
+        let mut view_box = ViewBox::new(rect(0.0, 0.0, 50.0, 50.0), rect(0.0, 0.0, 100.0, 100.0));
+        let fix_point = SvgPoint::new(20.0, 30.0);
+        let content = view_box.canvas_to_content().apply(fix_point);
+        view_box.set_rotation_around(std::f64::consts::FRAC_PI_2, fix_point);
+        assert_relative_eq!(view_box.scale(), 2.0);
+        let top_left = view_box.top_left();
+        let shown = view_box
+            .canvas_to_content()
+            .apply(fix_point + (top_left - SvgPoint::new(0.0, 0.0)));
+        assert_relative_eq!(shown.x, content.x, epsilon = 1e-9);
+        assert_relative_eq!(shown.y, content.y, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn rotated_content_bounds_enclose_the_content_box() {
+        let mut view_box = ViewBox::new(rect(0.0, 0.0, 50.0, 50.0), rect(0.0, 0.0, 100.0, 50.0));
+        assert_eq!(view_box.content_bounds(), view_box.content_box());
+        view_box.set_rotation(std::f64::consts::FRAC_PI_2);
+        let bounds = view_box.content_bounds();
+        assert_relative_eq!(bounds.left(), 25.0, epsilon = 1e-9);
+        assert_relative_eq!(bounds.top(), -25.0, epsilon = 1e-9);
+        assert_relative_eq!(bounds.width(), 50.0, epsilon = 1e-9);
+        assert_relative_eq!(bounds.height(), 100.0, epsilon = 1e-9);
+        assert_eq!(view_box.content_transform(), "rotate(90 50 25)");
+    }
+}
@@ -38,15 +38,17 @@ impl AsElement for ViewBox {
         id: impl Into<Option<&'a str>>,
         class: impl Into<Option<&'a str>>,
     ) -> Self::DomType {
-        // We use the default preserveAspectRatio="xMidYMid meet".
-        // It shrinks the viewBox to fit the view port and centers it.
         let mut el = RawSvgEl::new("svg")
             .attr("version", "1.1")
             .attr("xmlns", "http://www.w3.org/2000/svg")
             .attr("width", "100%")
             .attr("height", "100%")
             .style("display", "block")
-            .attr("viewBox", &self.to_svg_string());
+            .attr("viewBox", &self.to_svg_string())
+            .attr(
+                "preserveAspectRatio",
+                &self.preserve_aspect_ratio().to_svg_string(),
+            );
 
         let view_box = RawSvgEl::new("rect")
             .attr("x", &self.view_box().left().to_string())
@@ -72,9 +74,6 @@ impl<S: Signal<Item = ViewBox> + Unpin + 'static> IntoElementWithAttributeSignal
         id: impl Into<Option<&'a str>>,
         class: impl Into<Option<&'a str>>,
     ) -> Self::DomType {
-        // We use the default preserveAspectRatio="xMidYMid meet".
-        // It shrinks the viewBox to fit the view port and centers it.
-
         let signal_broadcaster = Broadcaster::new(self);
 
         let mut el = RawSvgEl::new("svg")
@@ -87,6 +86,10 @@ impl<S: Signal<Item = ViewBox> + Unpin + 'static> IntoElementWithAttributeSignal
                 "viewBox",
                 signal_broadcaster.signal_ref(ViewBox::to_svg_string),
             )
+            .attr_signal(
+                "preserveAspectRatio",
+                signal_broadcaster.signal_ref(|vb| vb.preserve_aspect_ratio().to_svg_string()),
+            )
             .child_signal(signal_broadcaster.signal_ref(|vb| {
                 RawSvgEl::new("rect")
                     .attr("x", &vb.view_box().left().to_string())
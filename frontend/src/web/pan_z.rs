@@ -1,139 +1,240 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+
 use approx::relative_eq;
+use num_traits::Zero;
 
 use wasm_bindgen::JsCast;
-use web_sys::SvgElement;
-use zoon::dominator::{with_node, EventOptions};
+use zoon::dominator::EventOptions;
 use zoon::events_extra::WheelEvent;
 use zoon::RawEl;
 use zoon::*;
 
 use crate::ViewBox;
+pub use animation::{animate_to, AnimationEnd, AnimationHandle, Easing, Interpolate};
+pub use deep_link::{deep_link, UrlEncode};
+pub use handle::PanZoomHandle;
+use keyboard::set_keyboard_event_listener;
+pub use limits::{PanBounds, PanZoomLimits};
+pub use minimap::minimap;
+use momentum::Momentum;
+pub use momentum::MomentumConfig;
+#[cfg(feature = "persistence")]
+pub use persistence::{persist_view, restore_view, StorageKind, ValidateRestored};
+use pointer::set_gesture_event_listener;
+pub use resize::{sync_view_box_with_view_port, ViewPortSync};
 pub use screen_geom::*;
-pub use state::view_state::view_state;
+pub use state::element_transform::ElementTransform;
+pub use state::view_state::ViewState;
 use state::PanZoomState;
+pub use view_history::{HistoryView, ViewHistory};
 
+mod animation;
+mod deep_link;
+mod events;
+mod handle;
+mod keyboard;
+mod limits;
+mod minimap;
+mod momentum;
+#[cfg(feature = "persistence")]
+mod persistence;
+mod pointer;
+mod resize;
 mod screen_geom;
 mod state;
+mod view_history;
 const ZOOM_SPEED_FACTOR: f64 = 0.05;
+/// In radians per wheel delta unit
+const ROTATION_SPEED_FACTOR: f64 = 0.002;
 
-/// Safety: `el` must be created from a signal of `view_box` and have the
-/// default value for the `preserveAspectRatio` attribute, which is `"xMidYMid
-/// meet"`
-pub unsafe fn enable_zooming_svg_view_box(
+/// Pans and zooms `el` by changing `view_box`.
+///
+/// `el` must be created from a signal of `view_box` by
+/// `IntoElementWithAttributeSignal`, so that its `viewBox` and
+/// `preserveAspectRatio` attributes follow `view_box`. Any
+/// `PreserveAspectRatio` works, as the screen geometry is taken from the
+/// browser. `PanZoomHandle::svg_view_box` creates such an element.
+///
+/// Rotating `view_box` only rotates content, that is wrapped in a `<g>`, whose
+/// `transform` attribute follows `ViewBox::content_transform`.
+/// `PanZoomHandle::svg_view_box` creates this wrapper, too.
+///
+/// `momentum` configures the movement after a gesture, `None` is
+/// `MomentumConfig::default()`.
+pub fn enable_zooming_svg_view_box(
     el: RawSvgEl,
-    view_box: &'static Mutable<ViewBox>,
+    view_box: Mutable<ViewBox>,
+    momentum: impl Into<Option<MomentumConfig>>,
 ) -> RawSvgEl {
-    el.update_dom_builder(|builder| {
-        let builder: DomBuilder<SvgElement> = builder;
-        set_zoom_event_listener(builder, view_box)
-    })
+    PanZoomHandle::bind(el, view_box, momentum.into().unwrap_or_default()).1
 }
 
-pub fn enable_zooming_svg_element<PZ: PanZoomState<SvgElement>>(
-    _el: RawSvgEl,
-    _state: &Mutable<SvgElement>,
+/// See `PanZoomHandle::svg_element`
+pub fn enable_zooming_svg_element(
+    el: RawSvgEl,
+    state: Mutable<ElementTransform>,
+    momentum: impl Into<Option<MomentumConfig>>,
 ) -> RawSvgEl {
-    todo!("implement either like the html version or the view box version")
+    PanZoomHandle::svg_element(el, state, momentum).1
 }
 
-pub fn enable_zooming_html_element<PZ: PanZoomState<web_sys::HtmlElement>>(
+/// See `PanZoomHandle::html_element`
+pub fn enable_zooming_html_element<PZ: PanZoomState<web_sys::HtmlElement> + Clone>(
     el: RawHtmlEl,
-    state: &'static Mutable<PZ>,
+    state: Mutable<PZ>,
+    momentum: impl Into<Option<MomentumConfig>>,
 ) -> RawHtmlEl {
-    el.update_dom_builder(|builder| {
-        let element = builder.__internal_element();
-        let builder = builder.style("transform-origin", "0 0").style_signal(
-            "transform",
-            state.signal_ref(move |view_state| {
-                warn!("view state: {}", view_state);
-                let top_left_pos = view_state.top_left(element.clone());
-                format!(
-                    "translate({}px, {}px) scale({})",
-                    top_left_pos.x(),
-                    top_left_pos.y(),
-                    view_state.scale()
-                )
-            }),
-        );
-        set_zoom_event_listener(builder, state)
-    })
+    PanZoomHandle::html_element(el, state, momentum).1
+}
+
+fn set_pan_zoom_event_listeners<
+    A: Clone + screen_geom::PositionedExtent + AsRef<web_sys::Element> + 'static,
+    PZ: PanZoomState<A> + Clone,
+>(
+    builder: DomBuilder<A>,
+    handle: PanZoomHandle<A, PZ>,
+    momentum: MomentumConfig,
+) -> DomBuilder<A> {
+    let momentum = Momentum::new(handle.clone(), momentum);
+    let builder = set_zoom_event_listener(builder, handle.clone(), momentum.clone());
+    let builder = set_gesture_event_listener(builder, handle.clone(), momentum.clone());
+    let builder = set_keyboard_event_listener(builder, handle, momentum.clone());
+    // The event listeners are removed together with the element, but frames already
+    // requested by the momentum would go on changing the state.
+    builder.after_removed(move |_| momentum.stop())
 }
 
+/// Zooms by Ctrl + wheel, rotates by Alt + wheel and pans by wheel -- or
+/// trackpad scrolling -- over the element.
+///
+/// The default action, i.e. scrolling or zooming the page, is only prevented
+/// if the event is consumed: Ctrl + wheel always is, Alt + wheel if the state
+/// can rotate, plain wheel only, if it actually pans. At the pan bounds, the
+/// page scrolls on.
 fn set_zoom_event_listener<
     A: Clone + screen_geom::PositionedExtent + 'static,
-    PZ: PanZoomState<A>,
+    PZ: PanZoomState<A> + Clone,
 >(
     builder: DomBuilder<A>,
-    // state must have a 'static lifetime, as the event listener might live for the rest of
-    // eternity
-    state: &'static Mutable<PZ>,
+    handle: PanZoomHandle<A, PZ>,
+    momentum: Momentum<A, PZ>,
 ) -> DomBuilder<A> {
-    with_node!(builder,zoom_element => {
-    .global_event_with_options(&EventOptions::preventable(),move |e: WheelEvent| {
+    builder.event_with_options(&EventOptions::preventable(), move |e: WheelEvent| {
+        // The event may come from a descendant outside of the element's bounds, e.g.
+        // overflowing content.
+        if !handle.element().bounding_rect().contains(e.pos()) {
+            return;
+        }
+        if e.ctrl_key() {
+            // the browser must not zoom the page
             e.prevent_default();
-            if e.ctrl_key(){
             // zooming by delta_y of mouse wheel
-                  let zoom_element_bounds = state.lock_ref().bounding_rect(zoom_element.clone());
+            let fix_point = e.pos();
+            let zoom_amount = -e.delta_y() * ZOOM_SPEED_FACTOR;
+            warn!("Zooming by {}% with fixpoint {}", zoom_amount, fix_point);
+            if zoom_amount != 0.0 {
+                // The mouse wheel determines the force of the zoom, not its amount. The
+                // momentum applies it over the next animation frames.
+                momentum.push_zoom_impulse((zoom_amount / 100.0).exp(), fix_point)
+            }
+        } else if e.alt_key() {
+            // rotating by delta_y of mouse wheel around the pointer, scrolling down turns
+            // clockwise
+            if !handle.can_rotate() {
+                return;
+            }
+            e.prevent_default();
+            momentum.stop();
+            let angle = e.delta_y() * ROTATION_SPEED_FACTOR;
+            if angle != 0.0 {
+                handle.rotate_by(e.pos(), angle);
+            }
+        } else {
+            // panning by delta_x or delta_y of mouse wheel
+            momentum.stop();
+            // scrolling down moves the content up
+            let pan_delta = ScreenVec::new(-e.delta_x(), -e.delta_y());
+            if !pan_delta.is_zero() {
+                let old_state = handle.state().get_cloned();
+                handle.pan_by(pan_delta);
+                if *handle.state().lock_ref() != old_state {
+                    e.prevent_default();
+                }
+            }
+        }
+    })
+}
 
-                let fix_point = e.pos();
-                let zoom_amount = -e.delta_y() * ZOOM_SPEED_FACTOR;
-    warn!("Zooming by {}% with fixpoint {}", zoom_amount, fix_point);
+/// Warns, if zooming around `fix_point` -- right after this call -- does not
+/// leave `fix_point` at the same position relative to the zoomed element.
+///
+/// The check is made two animation frames later, when the DOM has been
+/// updated. It is skipped, if `is_still_valid` returns `false` by then, e.g.
+/// because the fix point changed in between.
+///
+/// It only warns, as the pan bounds of the limits legitimately move the fix
+/// point, e.g. when zooming out near the bounds.
+fn check_fix_point_after_redraw<
+    A: Clone + screen_geom::PositionedExtent + 'static,
+    PZ: PanZoomState<A>,
+>(
+    handle: PanZoomHandle<A, PZ>,
+    fix_point: ViewPortPos,
+    is_still_valid: impl Fn() -> bool + 'static,
+) {
+    let zoom_element_bounds = handle.bounding_rect();
     let rect_relative_fix_point_offset: ScreenVec =
         zoom_element_bounds.rect_size_relative_offset(fix_point);
+    let unscaled_dimensions: (f64, f64) = handle.unscaled_dimensions();
+
+    let func = move || {
+        if !is_still_valid() {
+            return;
+        }
+        let new_zoon_element_bounds = handle.bounding_rect();
+        let new_unscaled_dimensions: (f64, f64) = handle.unscaled_dimensions();
+        if new_zoon_element_bounds == zoom_element_bounds {
+            // the scale did not change noticeably, nothing to check
+            return;
+        }
 
-    let unscaled_dimensions: (f64, f64) =
-        state.lock_ref().unscaled_dimensions(zoom_element.clone());
-    if zoom_amount != 0.0 {
-        //TODO: use the mouse wheel to determine a force of scroll (instead of
-        // amount). Instead a velocity tracker to control the mass of the "object" and
-        // the friction of the movement.
-        let new_scale = f64::max(state.lock_ref().scale() * (1.0 + zoom_amount / 100.0), 0.0);
-        state
-            .lock_mut()
-            .set_scale(zoom_element.clone(), fix_point, new_scale)
-    }
-
-    if cfg!(debug_assertions) {
-        let zoom_element = zoom_element.clone();
-
-
-        let func = move || {
-            warn!("Locking zoom state in animation frame");
-            let this = state.lock_ref();
-            let new_zoon_element_bounds = this.bounding_rect(zoom_element.clone());
-            let new_unscaled_dimensions: (f64, f64) =
-                this.unscaled_dimensions(zoom_element.clone());
-            info!("Lock freed");
-            assert_ne!(new_zoon_element_bounds, zoom_element_bounds);
-
-            assert!(is_pair_approx_eq_f32_epsilon(new_unscaled_dimensions, unscaled_dimensions),
+        if !is_pair_approx_eq_f32_epsilon(new_unscaled_dimensions, unscaled_dimensions) {
+            warn!(
                 "Unscaled dimensions new {:?} != old {:?}",
-                new_unscaled_dimensions,
-                unscaled_dimensions
+                new_unscaled_dimensions, unscaled_dimensions
             );
-            let new_rect_relative_fix_point_offset =
-                new_zoon_element_bounds.rect_size_relative_offset(fix_point);
-            let relative_pointer_offset_diff =
-                new_rect_relative_fix_point_offset - rect_relative_fix_point_offset;
-
-            assert!(is_screen_vec_approx_eq_f32_epsilon(new_rect_relative_fix_point_offset,rect_relative_fix_point_offset),
-                "\nrelative pointer offset must be a fix point, i.e new == old.\n\
-                order of magnitude: ({}, {})\n\
-                absolute unzoomed diff: ({}, {})",
-                f64::max(new_rect_relative_fix_point_offset.x(), rect_relative_fix_point_offset.x()),
-                f64::max(new_rect_relative_fix_point_offset.y(), rect_relative_fix_point_offset.y()),
-                relative_pointer_offset_diff.x() * new_unscaled_dimensions.0,
-                relative_pointer_offset_diff.y() * new_unscaled_dimensions.1
-            );
-        };
-        after_redraw(func)
-    }
-            } else {
-            // panning by delta_x or delta_y of mouse wheel
-                todo!()
-            }
-        })
-    })
+        }
+        let new_rect_relative_fix_point_offset =
+            new_zoon_element_bounds.rect_size_relative_offset(fix_point);
+        let relative_pointer_offset_diff =
+            new_rect_relative_fix_point_offset - rect_relative_fix_point_offset;
+
+        if is_screen_vec_approx_eq_f32_epsilon(
+            new_rect_relative_fix_point_offset,
+            rect_relative_fix_point_offset,
+        ) {
+            return;
+        }
+        warn!(
+            "\nrelative pointer offset did not stay a fix point, i.e new != old. \
+            The pan bounds may have moved it.\n\
+            order of magnitude: ({}, {})\n\
+            absolute unzoomed diff: ({}, {})",
+            f64::max(
+                new_rect_relative_fix_point_offset.x(),
+                rect_relative_fix_point_offset.x()
+            ),
+            f64::max(
+                new_rect_relative_fix_point_offset.y(),
+                rect_relative_fix_point_offset.y()
+            ),
+            relative_pointer_offset_diff.x() * new_unscaled_dimensions.0,
+            relative_pointer_offset_diff.y() * new_unscaled_dimensions.1
+        );
+    };
+    after_redraw(func)
 }
 
 fn is_pair_approx_eq_f32_epsilon(a: (f64, f64), b: (f64, f64)) -> bool {
@@ -180,3 +281,39 @@ fn after_redraw<F: FnMut() + 'static>(mut func: F) {
         .request_animation_frame(before_next_redraw.as_ref().unchecked_ref())
         .unwrap();
 }
+
+/// Calls `func` before the next redraw with the time passed to
+/// `request_animation_frame` callbacks, i.e. milliseconds since document
+/// creation.
+fn on_next_animation_frame<F: FnOnce(f64) + 'static>(func: F) {
+    // unlike a leaked `Closure`, this one is freed after being called
+    let on_frame = Closure::once_into_js(func);
+    let _request_id: i32 = window()
+        .request_animation_frame(on_frame.unchecked_ref())
+        .unwrap();
+}
+
+/// Calls `on_settled` with the latest value of `signal`, once it has not
+/// changed for `debounce_ms` milliseconds -- e.g. once per gesture instead of
+/// once per frame.
+///
+/// The returned future must be driven, e.g. by `DomBuilder::future`. A
+/// pending call is dropped with it, e.g. when the element is removed.
+fn for_each_settled<T: 'static>(
+    signal: impl Signal<Item = T> + 'static,
+    debounce_ms: u32,
+    on_settled: impl FnMut(T) + 'static,
+) -> impl Future<Output = ()> {
+    let on_settled = Rc::new(RefCell::new(on_settled));
+    // Replacing the handle with the next change -- or dropping it with the
+    // future -- aborts the pending call.
+    let mut pending: Option<TaskHandle> = None;
+    signal.for_each(move |value| {
+        let on_settled = on_settled.clone();
+        pending = Some(Task::start_droppable(async move {
+            Timer::sleep(debounce_ms).await;
+            (on_settled.borrow_mut())(value);
+        }));
+        async {}
+    })
+}
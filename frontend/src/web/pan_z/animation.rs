@@ -0,0 +1,188 @@
+use std::f64::consts::{PI, TAU};
+
+use zoon::*;
+
+use crate::ViewBox;
+
+/// States, that can be animated by `animate_to`.
+pub trait Interpolate {
+    /// Returns the state `t` of the way from `self` to `target`, i.e. `self`
+    /// for `t == 0.0` and `target` for `t == 1.0`.
+    fn interpolate(&self, target: &Self, t: f64) -> Self;
+}
+
+/// Interpolates the scale geometrically -- so that zooming appears to have a
+/// constant speed --, the rotation the shorter way round and the content shown
+/// in the center of the visible part linearly. Content box and limits are the
+/// target's.
+impl Interpolate for ViewBox {
+    fn interpolate(&self, target: &Self, t: f64) -> Self {
+        let scale = self.scale().powf(1.0 - t) * target.scale().powf(t);
+        let rotation = interpolate_angle(self.rotation(), target.rotation(), t);
+        let from = self.canvas_to_content().apply(self.visible_rect().center());
+        let to = target
+            .canvas_to_content()
+            .apply(target.visible_rect().center());
+        let center = from + (to - from) * t;
+        let mut view_box = *target;
+        view_box.set_scale(scale);
+        view_box.set_rotation(rotation);
+        view_box.center_visible_at(view_box.content_to_canvas().apply(center));
+        view_box
+    }
+}
+
+/// Returns the angle `t` of the way from `from` to `to`, in radians -- turning
+/// the shorter way round, e.g. from 350° to 10° through 0°, not 180°. For
+/// `t == 1.0` the result equals `to` up to full turns.
+pub(crate) fn interpolate_angle(from: f64, to: f64, t: f64) -> f64 {
+    let difference = (to - from).rem_euclid(TAU);
+    let shortest_difference = if difference > PI {
+        difference - TAU
+    } else {
+        difference
+    };
+    from + shortest_difference * t
+}
+
+/// Maps the linear progress of an animation in `0.0..=1.0` to the progress of
+/// the animated value in `0.0..=1.0`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Cubic for all but `Linear`
+    pub fn apply(&self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AnimationEnd {
+    /// The state reached the target.
+    Completed,
+    /// Either `AnimationHandle::cancel` was called or someone else -- e.g.
+    /// user input or another animation -- changed the state.
+    Cancelled,
+}
+
+/// Cancels the animation started by `animate_to`.
+#[derive(Debug, Clone)]
+pub struct AnimationHandle {
+    cancelled: Mutable<bool>,
+}
+
+impl AnimationHandle {
+    /// Stops the animation before its next frame. The state keeps its current
+    /// value.
+    pub fn cancel(&self) {
+        self.cancelled.set(true);
+    }
+}
+
+/// Changes `state` from its current value to `target` within `duration_ms`
+/// milliseconds -- frame by frame, as driven by `request_animation_frame`.
+///
+/// The animation is cancelled, as soon as anyone else changes the state, e.g.
+/// user input. `on_end` is called once, when the animation completes or is
+/// cancelled.
+pub fn animate_to<PZ: Interpolate + PartialEq + Clone + 'static>(
+    state: &Mutable<PZ>,
+    target: PZ,
+    duration_ms: f64,
+    easing: Easing,
+    on_end: impl FnOnce(AnimationEnd) + 'static,
+) -> AnimationHandle {
+    let cancelled = Mutable::new(false);
+    let start = state.get_cloned();
+    let animation = Animation {
+        // the animation might outlive the caller
+        state: state.clone(),
+        last_set: start.clone(),
+        start,
+        target,
+        duration_ms,
+        easing,
+        start_time: None,
+        cancelled: cancelled.clone(),
+        on_end: Box::new(on_end),
+    };
+    super::on_next_animation_frame(move |time| animation.frame(time));
+    AnimationHandle { cancelled }
+}
+
+struct Animation<PZ: 'static> {
+    state: Mutable<PZ>,
+    start: PZ,
+    target: PZ,
+    /// The value this animation set last. If the state differs, someone else
+    /// changed it.
+    last_set: PZ,
+    duration_ms: f64,
+    easing: Easing,
+    start_time: Option<f64>,
+    cancelled: Mutable<bool>,
+    on_end: Box<dyn FnOnce(AnimationEnd)>,
+}
+
+impl<PZ: Interpolate + PartialEq + Clone + 'static> Animation<PZ> {
+    fn frame(mut self, time: f64) {
+        if self.cancelled.get() || *self.state.lock_ref() != self.last_set {
+            info!("Animation cancelled");
+            (self.on_end)(AnimationEnd::Cancelled);
+            return;
+        }
+        let start_time = *self.start_time.get_or_insert(time);
+        let progress = if self.duration_ms > 0.0 {
+            f64::min((time - start_time) / self.duration_ms, 1.0)
+        } else {
+            1.0
+        };
+
+        if progress >= 1.0 {
+            self.state.set(self.target.clone());
+            info!("Animation completed");
+            (self.on_end)(AnimationEnd::Completed);
+            return;
+        }
+        let value = self
+            .start
+            .interpolate(&self.target, self.easing.apply(progress));
+        self.last_set = value.clone();
+        self.state.set(value);
+        super::on_next_animation_frame(move |time| self.frame(time));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolate_angle_turns_the_shorter_way() {
+        let (from, to) = (350f64.to_radians(), 10f64.to_radians());
+        assert_relative_eq!(interpolate_angle(from, to, 0.0), from);
+        assert_relative_eq!(interpolate_angle(from, to, 0.5), TAU, epsilon = 1e-12);
+        assert_relative_eq!(interpolate_angle(from, to, 1.0), TAU + to, epsilon = 1e-12);
+        assert_relative_eq!(interpolate_angle(to, from, 0.5), 0.0, epsilon = 1e-12);
+        assert_relative_eq!(interpolate_angle(0.0, 1.0, 0.25), 0.25, epsilon = 1e-12);
+        // several turns apart is no turn at all
+        assert_relative_eq!(interpolate_angle(0.0, 3.0 * TAU, 0.5), 0.0, epsilon = 1e-12);
+    }
+}
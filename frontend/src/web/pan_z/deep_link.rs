@@ -0,0 +1,281 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::JsValue;
+use zoon::*;
+
+use crate::svg::SvgPoint;
+use crate::web::pan_z::events::{HashChange, PopState};
+use crate::web::pan_z::view_history::HistoryView;
+use crate::ViewBox;
+
+/// A settled view replaces the current history entry, unless the view of that
+/// entry was shown for at least this many milliseconds.
+const HISTORY_IDLE_MS: f64 = 2000.0;
+
+/// States of element `A`, that can be encoded in the URL by `deep_link`.
+///
+/// The encoding should describe the content shown, not the state's raw
+/// values, so that a link shows the same content in view ports of any size.
+pub trait UrlEncode<A>: Sized {
+    /// Returns a short, human-readable encoding of the view, e.g.
+    /// `"12.5,-3,2"` -- or `None`, if the element does not show it, e.g. as it
+    /// is not rendered. `deep_link` percent-encodes it in the URL.
+    fn to_url_value(&self, element: &A) -> Option<String>;
+
+    /// Returns `current` changed to show the view encoded in `value` -- or
+    /// `None`, if `value` is malformed or the element cannot show it. Content
+    /// and limits are `current`'s.
+    fn from_url_value(current: &Self, element: &A, value: &str) -> Option<Self>;
+}
+
+/// `x,y,scale,rotation`: the point of the content shown in the center of the
+/// visible part, scale and rotation in radians -- so that the link shows the
+/// same part of the content in view ports of any shape. Links without the
+/// rotation, `x,y,scale`, are not rotated.
+impl<A> UrlEncode<A> for ViewBox {
+    fn to_url_value(&self, _element: &A) -> Option<String> {
+        let center = self.canvas_to_content().apply(self.visible_rect().center());
+        Some(format!(
+            "{},{},{},{}",
+            center.x,
+            center.y,
+            self.scale(),
+            self.rotation()
+        ))
+    }
+
+    fn from_url_value(current: &Self, _element: &A, value: &str) -> Option<Self> {
+        let [x, y, scale, rotation] = parse_numbers(value).or_else(|| {
+            let [x, y, scale] = parse_numbers(value)?;
+            Some([x, y, scale, 0.0])
+        })?;
+        if scale <= 0.0 {
+            return None;
+        }
+        let mut view_box = *current;
+        view_box.set_scale(scale);
+        view_box.set_rotation(rotation);
+        view_box.center_visible_at(view_box.content_to_canvas().apply(SvgPoint::new(x, y)));
+        Some(view_box)
+    }
+}
+
+/// Parses `N` comma-separated finite numbers.
+pub(crate) fn parse_numbers<const N: usize>(value: &str) -> Option<[f64; N]> {
+    let mut numbers = [0.0; N];
+    let mut parts = value.split(',');
+    for number in numbers.iter_mut() {
+        *number = parts.next()?.trim().parse().ok()?;
+        if !number.is_finite() {
+            return None;
+        }
+    }
+    match parts.next() {
+        None => Some(numbers),
+        Some(_) => None,
+    }
+}
+
+/// Mirrors `state` into the fragment of the URL as `#key=value` -- next to the
+/// entries of other viewers -- and applies the view encoded there, so links
+/// open the viewer at the same view.
+///
+/// The view in the URL is applied, once `el` is inserted, and whenever the
+/// user navigates, e.g. by the back and forward buttons or by following a
+/// link. Changes of `state` are written, once they settled for `debounce_ms`
+/// milliseconds, i.e. once per gesture. A settled view adds a history entry
+/// only, if the view it leaves was shown for a while -- see
+/// `HISTORY_IDLE_MS` --, otherwise it replaces the current entry. Thus the
+/// back button returns to the previous view, the user rested on, not to each
+/// step of a series of gestures. The initial view and adaptations to the view
+/// port -- see `HistoryView` -- replace the current entry, too.
+///
+/// `key` must be unique per viewer. Navigation is no longer followed, when
+/// `el` is removed from the DOM.
+pub fn deep_link<E: RawEl, PZ: UrlEncode<E::DomElement> + HistoryView + 'static>(
+    el: E,
+    state: Mutable<PZ>,
+    key: &str,
+    debounce_ms: u32,
+) -> E
+where
+    E::DomElement: 'static,
+{
+    let link = Rc::new(RefCell::new(Link {
+        key: key.to_string(),
+        inserted: false,
+        last: None,
+        written_at: 0.0,
+        left_at: None,
+    }));
+    el.update_dom_builder(move |builder| {
+        let element = builder.__internal_element();
+        let builder = builder
+            .after_inserted({
+                let (state, link) = (state.clone(), link.clone());
+                move |element| {
+                    let mut link = link.borrow_mut();
+                    link.inserted = true;
+                    link.apply_url(&state, &element)
+                }
+            })
+            .global_event({
+                let (state, link, element) = (state.clone(), link.clone(), element.clone());
+                move |_: PopState| link.borrow_mut().apply_url(&state, &element)
+            })
+            .global_event({
+                let (state, link, element) = (state.clone(), link.clone(), element.clone());
+                move |_: HashChange| link.borrow_mut().apply_url(&state, &element)
+            })
+            .future({
+                let link = link.clone();
+                state
+                    .signal_ref(move |view| link.borrow_mut().note_change(view))
+                    .for_each(|()| async {})
+            });
+        builder.future(super::for_each_settled(
+            state.signal_cloned(),
+            debounce_ms,
+            move |value| link.borrow_mut().write_url(&value, &element),
+        ))
+    })
+}
+
+/// The state of `deep_link` for a single viewer
+struct Link<PZ> {
+    key: String,
+    /// The encodings depend on the geometry of the element, so nothing is
+    /// written, before it is inserted.
+    inserted: bool,
+    /// The view last applied from or written to the URL
+    last: Option<PZ>,
+    /// When `last` was applied or written, in milliseconds since the epoch
+    written_at: f64,
+    /// When `state` first changed away from `last`
+    left_at: Option<f64>,
+}
+
+impl<PZ: HistoryView> Link<PZ> {
+    /// Sets `state` to the view encoded under the key in the URL, if there is
+    /// one.
+    fn apply_url<A>(&mut self, state: &Mutable<PZ>, element: &A)
+    where
+        PZ: UrlEncode<A>,
+    {
+        let value = match fragment_entries().into_iter().find(|(k, _)| *k == self.key) {
+            Some((_, value)) => value,
+            None => return,
+        };
+        let current = state.get_cloned();
+        match PZ::from_url_value(&current, element, &value) {
+            Some(linked) if linked != current => {
+                info!("Applying view {}={} from the URL", self.key, value);
+                self.shown(linked.clone());
+                state.set(linked)
+            }
+            Some(_) => self.shown(current),
+            None => warn!(
+                "Ignoring view {}={} in the URL, it is malformed or cannot be shown",
+                self.key, value
+            ),
+        }
+    }
+
+    /// Writes the settled `view` to the URL: It pushes a new history entry, if
+    /// the last view was shown for `HISTORY_IDLE_MS`. The first view,
+    /// adaptations of the last one and views following a short-lived one
+    /// replace the current entry. Views applied from the URL are not written
+    /// back.
+    fn write_url<A>(&mut self, view: &PZ, element: &A)
+    where
+        PZ: UrlEncode<A>,
+    {
+        if !self.inserted {
+            return;
+        }
+        let now = js_sys::Date::now();
+        let (push, adaptation) = match &self.last {
+            Some(last) if last == view => return,
+            Some(last) => {
+                let adaptation = view.is_adaptation_of(last);
+                let shown_for = self.left_at.unwrap_or(now) - self.written_at;
+                (!adaptation && shown_for >= HISTORY_IDLE_MS, adaptation)
+            }
+            None => (false, false),
+        };
+        let value = match view.to_url_value(element) {
+            Some(value) => value,
+            None => return,
+        };
+        let history = match window().history() {
+            Ok(history) => history,
+            Err(err) => {
+                warn!("Cannot write view {} to the URL: {:?}", self.key, err);
+                return;
+            }
+        };
+        if adaptation {
+            // still the same view, which has been shown since it was written
+            self.last = Some(view.clone());
+            self.left_at = None;
+        } else {
+            self.shown(view.clone());
+        }
+        let mut entries = fragment_entries();
+        match entries.iter_mut().find(|(k, _)| *k == self.key) {
+            Some(entry) if entry.1 == value => return,
+            Some(entry) => entry.1 = value,
+            None => entries.push((self.key.clone(), value)),
+        }
+        let fragment = entries
+            .iter()
+            .map(|(k, v)| format!("{}={}", encode(k), encode(v)))
+            .collect::<Vec<String>>()
+            .join("&");
+        let url = format!("#{}", fragment);
+        let written = if push {
+            history.push_state_with_url(&JsValue::NULL, "", Some(&url))
+        } else {
+            history.replace_state_with_url(&JsValue::NULL, "", Some(&url))
+        };
+        if let Err(err) = written {
+            warn!("Cannot write view {} to the URL: {:?}", self.key, err);
+        }
+    }
+
+    /// Records, that the URL shows `view` from now on.
+    fn shown(&mut self, view: PZ) {
+        self.last = Some(view);
+        self.written_at = js_sys::Date::now();
+        self.left_at = None;
+    }
+
+    /// Records the first change of the state away from the view in the URL.
+    fn note_change(&mut self, view: &PZ) {
+        if self.left_at.is_none() && matches!(&self.last, Some(last) if last != view) {
+            self.left_at = Some(js_sys::Date::now());
+        }
+    }
+}
+
+/// The decoded `key=value` pairs of the URL's fragment. Pairs, that are not
+/// properly percent-encoded, are skipped.
+fn fragment_entries() -> Vec<(String, String)> {
+    let hash = window().location().hash().unwrap_or_default();
+    hash.trim_start_matches('#')
+        .split('&')
+        .filter_map(|entry| {
+            let (key, value) = entry.split_once('=')?;
+            Some((decode(key)?, decode(value)?))
+        })
+        .collect()
+}
+
+fn encode(component: &str) -> String {
+    js_sys::encode_uri_component(component).into()
+}
+
+fn decode(component: &str) -> Option<String> {
+    js_sys::decode_uri_component(component).ok()?.as_string()
+}
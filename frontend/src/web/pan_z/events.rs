@@ -0,0 +1,96 @@
+//! Pointer and navigation events missing in `zoon::events_extra`.
+//!
+//! # See
+//! * https://developer.mozilla.org/en-US/docs/Web/API/Pointer_event
+//! * https://developer.mozilla.org/en-US/docs/Web/API/Window/popstate_event
+use wasm_bindgen::JsCast;
+use zoon::dominator::traits::StaticEvent;
+
+use crate::web::pan_z::screen_geom::Positioned;
+use crate::ViewPortPos;
+
+/// Fired when an element receives pointer capture, e.g. after
+/// `Element::set_pointer_capture`.
+#[derive(Debug)]
+pub struct GotPointerCapture {
+    event: web_sys::PointerEvent,
+}
+
+impl StaticEvent for GotPointerCapture {
+    const EVENT_TYPE: &'static str = "gotpointercapture";
+
+    fn unchecked_from_event(event: web_sys::Event) -> Self {
+        Self {
+            event: event.unchecked_into(),
+        }
+    }
+}
+
+impl GotPointerCapture {
+    pub fn pointer_id(&self) -> i32 {
+        self.event.pointer_id()
+    }
+}
+
+impl Positioned for GotPointerCapture {
+    fn pos(&self) -> ViewPortPos {
+        self.event.pos()
+    }
+}
+
+/// Fired when an element loses pointer capture, i.e. after `pointerup`,
+/// `pointercancel`, `Element::release_pointer_capture` or when the element is
+/// removed from the document.
+#[derive(Debug)]
+pub struct LostPointerCapture {
+    event: web_sys::PointerEvent,
+}
+
+impl StaticEvent for LostPointerCapture {
+    const EVENT_TYPE: &'static str = "lostpointercapture";
+
+    fn unchecked_from_event(event: web_sys::Event) -> Self {
+        Self {
+            event: event.unchecked_into(),
+        }
+    }
+}
+
+impl LostPointerCapture {
+    pub fn pointer_id(&self) -> i32 {
+        self.event.pointer_id()
+    }
+}
+
+impl Positioned for LostPointerCapture {
+    fn pos(&self) -> ViewPortPos {
+        self.event.pos()
+    }
+}
+
+/// Fired at the window, when the active history entry changes by navigation,
+/// e.g. by the back and forward buttons. Not fired by
+/// `History::replace_state`.
+#[derive(Debug)]
+pub struct PopState;
+
+impl StaticEvent for PopState {
+    const EVENT_TYPE: &'static str = "popstate";
+
+    fn unchecked_from_event(_event: web_sys::Event) -> Self {
+        Self
+    }
+}
+
+/// Fired at the window, when the fragment of the URL changes, e.g. by following
+/// a link or editing the address bar.
+#[derive(Debug)]
+pub struct HashChange;
+
+impl StaticEvent for HashChange {
+    const EVENT_TYPE: &'static str = "hashchange";
+
+    fn unchecked_from_event(_event: web_sys::Event) -> Self {
+        Self
+    }
+}
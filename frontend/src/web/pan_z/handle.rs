@@ -0,0 +1,241 @@
+use web_sys::{HtmlElement, SvgElement};
+use zoon::*;
+
+use crate::svg::ToSvgString;
+use crate::web::pan_z::screen_geom::PositionedExtent;
+use crate::web::pan_z::state::PanZoomState;
+use crate::web::IntoElementWithAttributeSignal;
+use crate::{ElementTransform, MomentumConfig, ScreenVec, ViewBox, ViewPortPos, ViewPortRect};
+
+/// A pan-zoom state bound to the element, which is panned and zoomed by it.
+///
+/// The handle is created together with its element, so both always belong to
+/// each other. Its methods lock the state and pass the bound element to the
+/// `PanZoomState`.
+///
+/// Each handle owns a reference to its own `Mutable`, so any number of
+/// independent instances can be mounted and dropped. The event listeners are
+/// freed, when the element is removed.
+pub struct PanZoomHandle<A, PZ: 'static> {
+    state: Mutable<PZ>,
+    element: A,
+}
+
+impl<A: Clone, PZ> Clone for PanZoomHandle<A, PZ> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            element: self.element.clone(),
+        }
+    }
+}
+
+impl PanZoomHandle<SvgElement, ViewBox> {
+    /// Creates an svg-tag from a signal of `state` -- see
+    /// `IntoElementWithAttributeSignal` -- and pans, zooms and rotates it by
+    /// changing `state`.
+    ///
+    /// `content` adds the content to a `<g>` inside the svg-tag, which rotates
+    /// it by `ViewBox::content_transform`. Further children may be added to
+    /// the returned svg-tag; they are not rotated.
+    ///
+    /// `momentum` configures the movement after a gesture, `None` is
+    /// `MomentumConfig::default()`.
+    pub fn svg_view_box<'a>(
+        state: Mutable<ViewBox>,
+        id: impl Into<Option<&'a str>>,
+        class: impl Into<Option<&'a str>>,
+        momentum: impl Into<Option<MomentumConfig>>,
+        content: impl FnOnce(RawSvgEl) -> RawSvgEl,
+    ) -> (Self, RawSvgEl) {
+        let rotated_content = content(RawSvgEl::new("g"))
+            .attr_signal("transform", state.signal_ref(ViewBox::content_transform));
+        let el = state
+            .signal()
+            .into_element_with_attribute_signal((), id, class)
+            .child(rotated_content);
+        Self::bind(el, state, momentum.into().unwrap_or_default())
+    }
+}
+
+impl PanZoomHandle<SvgElement, ElementTransform> {
+    /// Pans and zooms `el` -- e.g. a `<g>` inside a larger SVG -- by writing
+    /// its `transform` attribute. The root `viewBox` is not touched.
+    ///
+    /// The `transform` attribute of `el` is overwritten. To keep an authored
+    /// transform, wrap `el` in another `<g>` and bind the wrapper.
+    ///
+    /// `momentum` configures the movement after a gesture, `None` is
+    /// `MomentumConfig::default()`.
+    pub fn svg_element(
+        el: RawSvgEl,
+        state: Mutable<ElementTransform>,
+        momentum: impl Into<Option<MomentumConfig>>,
+    ) -> (Self, RawSvgEl) {
+        let el = el.attr_signal(
+            "transform",
+            state.signal_ref(|element_transform| element_transform.to_svg_string()),
+        );
+        Self::bind(el, state, momentum.into().unwrap_or_default())
+    }
+}
+
+impl<PZ: PanZoomState<HtmlElement> + Clone> PanZoomHandle<HtmlElement, PZ> {
+    /// Pans, zooms and rotates `el` by its CSS `transform`.
+    ///
+    /// `momentum` configures the movement after a gesture, `None` is
+    /// `MomentumConfig::default()`.
+    pub fn html_element(
+        el: RawHtmlEl,
+        state: Mutable<PZ>,
+        momentum: impl Into<Option<MomentumConfig>>,
+    ) -> (Self, RawHtmlEl) {
+        let (handle, el) = Self::bind(el, state, momentum.into().unwrap_or_default());
+        let element = handle.element.clone();
+        let el = el.style("transform-origin", "0 0").style_signal(
+            "transform",
+            handle.state.signal_ref(move |view_state| {
+                warn!("view state: {}", view_state);
+                let top_left_pos = view_state.top_left(element.clone());
+                format!(
+                    "translate({}px, {}px) rotate({}rad) scale({})",
+                    top_left_pos.x(),
+                    top_left_pos.y(),
+                    view_state.rotation(),
+                    view_state.scale()
+                )
+            }),
+        );
+        (handle, el)
+    }
+}
+
+impl<A: Clone + PositionedExtent + 'static, PZ: PanZoomState<A>> PanZoomHandle<A, PZ> {
+    /// Binds `state` to the element of `el` and sets the event listeners for
+    /// panning and zooming, with `momentum` after gestures.
+    pub(super) fn bind<E: RawEl<DomElement = A>>(
+        el: E,
+        state: Mutable<PZ>,
+        momentum: MomentumConfig,
+    ) -> (Self, E)
+    where
+        A: AsRef<web_sys::Element>,
+        PZ: Clone,
+    {
+        let mut handle = None;
+        let el = el.update_dom_builder(|builder| {
+            let bound = Self {
+                state,
+                element: builder.__internal_element(),
+            };
+            handle = Some(bound.clone());
+            super::set_pan_zoom_event_listeners(builder, bound, momentum)
+        });
+        (
+            handle.expect("update_dom_builder calls the updater right away"),
+            el,
+        )
+    }
+
+    pub fn state(&self) -> &Mutable<PZ> {
+        &self.state
+    }
+
+    pub fn element(&self) -> &A {
+        &self.element
+    }
+
+    /// See `PanZoomState::as_local_coordinates`
+    pub fn as_local_coordinates(&self, point: ViewPortPos) -> Option<PZ::LocalPoint> {
+        self.state
+            .lock_ref()
+            .as_local_coordinates(self.element.clone(), point)
+    }
+
+    pub fn unscaled_dimensions(&self) -> (f64, f64) {
+        self.state
+            .lock_ref()
+            .unscaled_dimensions(self.element.clone())
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.state.lock_ref().scale()
+    }
+
+    pub fn rotation(&self) -> f64 {
+        self.state.lock_ref().rotation()
+    }
+
+    pub fn top_left(&self) -> ViewPortPos {
+        self.state.lock_ref().top_left(self.element.clone())
+    }
+
+    pub fn bounding_rect(&self) -> ViewPortRect {
+        self.state.lock_ref().bounding_rect(self.element.clone())
+    }
+
+    /// See `PanZoomState::set_scale`
+    pub fn set_scale(&self, fix_point: ViewPortPos, new_scale: f64) {
+        self.state
+            .lock_mut()
+            .set_scale(self.element.clone(), fix_point, new_scale)
+    }
+
+    /// Multiplies the scale by `scale_ratio` around `fix_point`.
+    pub fn zoom_by(&self, fix_point: ViewPortPos, scale_ratio: f64) {
+        let mut state = self.state.lock_mut();
+        let new_scale = state.scale() * scale_ratio;
+        state.set_scale(self.element.clone(), fix_point, new_scale)
+    }
+
+    /// See `PanZoomState::set_rotation`
+    pub fn set_rotation(&self, fix_point: ViewPortPos, new_rotation: f64) {
+        self.state
+            .lock_mut()
+            .set_rotation(self.element.clone(), fix_point, new_rotation)
+    }
+
+    /// Rotates by `angle` radians around `fix_point`.
+    pub fn rotate_by(&self, fix_point: ViewPortPos, angle: f64) {
+        let mut state = self.state.lock_mut();
+        let new_rotation = state.rotation() + angle;
+        state.set_rotation(self.element.clone(), fix_point, new_rotation)
+    }
+
+    /// See `PanZoomState::can_rotate`
+    pub fn can_rotate(&self) -> bool {
+        self.state.lock_ref().can_rotate()
+    }
+
+    /// Multiplies the scale by `scale_ratio`, rotates by `angle` radians and
+    /// moves the content shown at `fix_point` by `delta` -- in a single change
+    /// of the state, see `PanZoomState::set_transform`.
+    pub fn transform_by(
+        &self,
+        fix_point: ViewPortPos,
+        delta: ScreenVec,
+        scale_ratio: f64,
+        angle: f64,
+    ) {
+        let mut state = self.state.lock_mut();
+        let new_scale = state.scale() * scale_ratio;
+        let new_rotation = state.rotation() + angle;
+        state.set_transform(
+            self.element.clone(),
+            fix_point,
+            delta,
+            new_scale,
+            new_rotation,
+        )
+    }
+
+    /// See `PanZoomState::pan_by`
+    pub fn pan_by(&self, delta: ScreenVec) {
+        self.state.lock_mut().pan_by(self.element.clone(), delta)
+    }
+
+    /// See `PanZoomState::fit_content`
+    pub fn fit_content(&self) {
+        self.state.lock_mut().fit_content(self.element.clone())
+    }
+}
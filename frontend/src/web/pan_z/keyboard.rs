@@ -0,0 +1,64 @@
+use zoon::dominator::EventOptions;
+use zoon::events::KeyDown;
+use zoon::*;
+
+use crate::web::pan_z::momentum::Momentum;
+use crate::web::pan_z::screen_geom::PositionedExtent;
+use crate::web::pan_z::state::PanZoomState;
+use crate::{PanZoomHandle, ScreenVec};
+
+/// In view port units
+const KEYBOARD_PAN_STEP: f64 = 40.0;
+const KEYBOARD_ZOOM_FACTOR: f64 = 1.25;
+
+/// Makes the element focusable and navigable by keyboard:
+///
+/// * arrow keys pan, i.e. move the view -- not the content -- into the arrow's
+///   direction
+/// * `+` and `-` zoom around the center of the element
+/// * `0` resets to the view at the time of this call
+/// * `f` fits the content
+///
+/// Keys pressed together with Ctrl, Alt or Meta are left to the browser.
+pub(super) fn set_keyboard_event_listener<
+    A: Clone + PositionedExtent + 'static,
+    PZ: PanZoomState<A> + Clone,
+>(
+    builder: DomBuilder<A>,
+    handle: PanZoomHandle<A, PZ>,
+    momentum: Momentum<A, PZ>,
+) -> DomBuilder<A> {
+    let initial_state = handle.state().get_cloned();
+
+    builder.attr("tabindex", "0").event_with_options(
+        &EventOptions::preventable(),
+        move |e: KeyDown| {
+            if e.ctrl_key() || e.alt_key() || e.meta_key() {
+                return;
+            }
+            match e.key().as_str() {
+                "ArrowLeft" => handle.pan_by(ScreenVec::new(KEYBOARD_PAN_STEP, 0.0)),
+                "ArrowRight" => handle.pan_by(ScreenVec::new(-KEYBOARD_PAN_STEP, 0.0)),
+                "ArrowUp" => handle.pan_by(ScreenVec::new(0.0, KEYBOARD_PAN_STEP)),
+                "ArrowDown" => handle.pan_by(ScreenVec::new(0.0, -KEYBOARD_PAN_STEP)),
+                // `=` shares its key with `+` on many layouts
+                "+" | "=" => handle.zoom_by(
+                    handle.element().bounding_rect().center(),
+                    KEYBOARD_ZOOM_FACTOR,
+                ),
+                "-" => handle.zoom_by(
+                    handle.element().bounding_rect().center(),
+                    1.0 / KEYBOARD_ZOOM_FACTOR,
+                ),
+                "0" => {
+                    info!("Resetting to {}", initial_state);
+                    handle.state().set(initial_state.clone())
+                }
+                "f" => handle.fit_content(),
+                _ => return,
+            }
+            momentum.stop();
+            e.prevent_default();
+        },
+    )
+}
@@ -0,0 +1,126 @@
+use std::fmt::{Display, Formatter};
+
+/// Restricts how far a `PanZoomState` can be zoomed and panned.
+///
+/// The limits are enforced by the states themselves, so that no input --
+/// wheel, pointer, keyboard or a programmatic change -- gets past them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialization::LimitsV1",
+        try_from = "crate::serialization::LimitsV1"
+    )
+)]
+pub struct PanZoomLimits {
+    /// Must be greater than `0.0`, as the `ViewBox` divides by the scale.
+    pub min_scale: f64,
+    pub max_scale: f64,
+    pub pan_bounds: PanBounds,
+}
+
+impl Default for PanZoomLimits {
+    /// Beyond these scales, the fix point of zooming is no longer stable
+    /// within `f32` precision, which is the precision browsers compute the
+    /// geometry with.
+    fn default() -> Self {
+        Self {
+            min_scale: 0.001,
+            max_scale: 1000.0,
+            pan_bounds: PanBounds::Unbounded,
+        }
+    }
+}
+
+impl Display for PanZoomLimits {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Limits {{scale: {}..{}, pan: {:?}}}",
+            self.min_scale, self.max_scale, self.pan_bounds
+        )
+    }
+}
+
+impl PanZoomLimits {
+    pub fn new(min_scale: f64, max_scale: f64, pan_bounds: PanBounds) -> Self {
+        let limits = Self {
+            min_scale,
+            max_scale,
+            pan_bounds,
+        };
+        debug_assert!(limits.is_valid(), "Invalid {}", limits);
+        limits
+    }
+
+    /// Whether the scale range is positive and not empty and the pan bounds
+    /// are valid.
+    pub fn is_valid(&self) -> bool {
+        self.min_scale > 0.0
+            && self.min_scale.is_finite()
+            && self.min_scale <= self.max_scale
+            && self.pan_bounds.is_valid()
+    }
+
+    pub fn clamp_scale(&self, scale: f64) -> f64 {
+        scale.clamp(self.min_scale, self.max_scale)
+    }
+
+    /// Clamps the start `pos` of the one-dimensional extent `pos..pos + size`,
+    /// such that it overlaps the fixed extent `fixed_pos..fixed_pos +
+    /// fixed_size` as required by the pan bounds.
+    ///
+    /// The overlap is symmetric: Either extent may be the content, the other
+    /// one the visible area.
+    pub(crate) fn clamp_pan(&self, pos: f64, size: f64, fixed_pos: f64, fixed_size: f64) -> f64 {
+        match self.pan_bounds {
+            PanBounds::Unbounded => pos,
+            PanBounds::KeepContentVisible {
+                min_visible_fraction,
+            } => {
+                // The extents overlap by at least `min_overlap`, iff
+                // pos + size >= fixed_pos + min_overlap and
+                // pos <= fixed_pos + fixed_size - min_overlap
+                let min_overlap = min_visible_fraction.clamp(0.0, 1.0) * f64::min(size, fixed_size);
+                f64::min(
+                    f64::max(pos, fixed_pos + min_overlap - size),
+                    fixed_pos + fixed_size - min_overlap,
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialization::PanBoundsV1",
+        try_from = "crate::serialization::PanBoundsV1"
+    )
+)]
+pub enum PanBounds {
+    Unbounded,
+    /// Keeps the content at least partly visible: In each dimension, content
+    /// and visible area overlap by at least `min_visible_fraction` of the
+    /// smaller of the two. `1.0` keeps the content completely visible, if it
+    /// fits.
+    KeepContentVisible {
+        /// Within `0.0..=1.0`
+        min_visible_fraction: f64,
+    },
+}
+
+impl PanBounds {
+    /// Whether `min_visible_fraction` is within `0.0..=1.0`.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            PanBounds::Unbounded => true,
+            PanBounds::KeepContentVisible {
+                min_visible_fraction,
+            } => (0.0..=1.0).contains(min_visible_fraction),
+        }
+    }
+}
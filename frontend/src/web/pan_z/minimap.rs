@@ -0,0 +1,191 @@
+use wasm_bindgen::JsCast;
+use web_sys::{SvgElement, SvgGraphicsElement};
+use zoon::*;
+
+use crate::svg::{SvgPoint, SvgVec, ToSvgString};
+use crate::web::pan_z::pointer::{set_capture_drag_listeners, DragEnd};
+use crate::web::pan_z::screen_geom::screen_ctm;
+use crate::{ViewBox, ViewPortPos};
+
+/// A pointer dragging the view in the minimap
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Drag {
+    pointer_id: i32,
+    /// From the top left corner of the visible part of the view box to the
+    /// pointer
+    grab_offset: SvgVec,
+}
+
+/// Returns an overview of the content of `view_box`: an SVG, that always shows
+/// the whole content box, with the visible part of the view box drawn as a rect
+/// on top. The content is not rotated, the rect is rotated against it instead.
+///
+/// Dragging the rect pans `view_box`. Clicking anywhere else centers the view
+/// there, dragging on pans from there. The scale is kept and the pan bounds of
+/// the limits apply, as with any other pan.
+///
+/// `content` adds the content to the minimap's SVG, e.g. the same elements as
+/// in the main view. Like the main view, the minimap fills its parent, which
+/// determines its size.
+pub fn minimap(view_box: Mutable<ViewBox>, content: impl FnOnce(RawSvgEl) -> RawSvgEl) -> RawSvgEl {
+    let el = RawSvgEl::new("svg")
+        .attr("version", "1.1")
+        .attr("xmlns", "http://www.w3.org/2000/svg")
+        .attr("width", "100%")
+        .attr("height", "100%")
+        .style("display", "block")
+        .attr_signal(
+            "viewBox",
+            view_box.signal_ref(|vb| vb.content_box().to_svg_string()),
+        );
+    content(el)
+        .child(view_rect(&view_box))
+        .update_dom_builder(|builder| set_drag_event_listeners(builder, view_box))
+}
+
+/// The rect marking the visible part of the view box, above the content
+fn view_rect(view_box: &Mutable<ViewBox>) -> RawSvgEl {
+    RawSvgEl::new("rect")
+        .attr_signal(
+            "transform",
+            view_box.signal_ref(|vb| {
+                let center = vb.content_box().center();
+                format!(
+                    "rotate({} {} {})",
+                    -vb.rotation().to_degrees(),
+                    center.x,
+                    center.y
+                )
+            }),
+        )
+        .attr_signal(
+            "x",
+            view_box.signal_ref(|vb| vb.visible_rect().left().to_string()),
+        )
+        .attr_signal(
+            "y",
+            view_box.signal_ref(|vb| vb.visible_rect().top().to_string()),
+        )
+        .attr_signal(
+            "width",
+            view_box.signal_ref(|vb| vb.visible_rect().width().to_string()),
+        )
+        .attr_signal(
+            "height",
+            view_box.signal_ref(|vb| vb.visible_rect().height().to_string()),
+        )
+        // the stroke keeps its width, however small the minimap shows the content
+        .attr("vector-effect", "non-scaling-stroke")
+        .style("fill", "crimson")
+        .style("fill-opacity", "0.15")
+        .style("stroke", "crimson")
+        .style("cursor", "move")
+}
+
+/// Pans `view_box` by dragging a single pointer over the minimap. The pointer
+/// is captured by `set_capture_drag_listeners`, so the drag goes on, even if
+/// it leaves the minimap.
+fn set_drag_event_listeners(
+    builder: DomBuilder<SvgElement>,
+    view_box: Mutable<ViewBox>,
+) -> DomBuilder<SvgElement> {
+    let element = builder.__internal_element();
+    let drag: Mutable<Option<Drag>> = Mutable::new(None);
+
+    let on_down = {
+        let element = element.clone();
+        let view_box = view_box.clone();
+        let drag = drag.clone();
+        move |pointer_id: i32, pos: ViewPortPos| {
+            if drag.lock_ref().is_some() {
+                // a second pointer must not take over the drag
+                return false;
+            }
+            let pos = match to_canvas(&element, &view_box, pos) {
+                Some(pos) => pos,
+                None => return false,
+            };
+            let view = view_box.get().visible_rect();
+            let grab_offset = if view.contains(pos) {
+                pos - view.top_left()
+            } else {
+                view.dimensions() / 2.0
+            };
+            info!(
+                "Pointer {} drags the view at {} in the minimap",
+                pointer_id, pos
+            );
+            drag.set(Some(Drag {
+                pointer_id,
+                grab_offset,
+            }));
+            pan_visible_to(&view_box, pos - grab_offset);
+            true
+        }
+    };
+
+    let on_move = {
+        let drag = drag.clone();
+        move |pointer_id: i32, pos: ViewPortPos| {
+            let grab_offset = match *drag.lock_ref() {
+                Some(drag) if drag.pointer_id == pointer_id => drag.grab_offset,
+                _ => return,
+            };
+            if let Some(pos) = to_canvas(&element, &view_box, pos) {
+                pan_visible_to(&view_box, pos - grab_offset);
+            }
+        }
+    };
+
+    let on_end = move |pointer_id: i32, _: DragEnd| release_pointer(&drag, pointer_id);
+
+    set_capture_drag_listeners(builder, on_down, on_move, on_end)
+}
+
+/// Pans `view_box`, such that the top left corner of its visible part is at
+/// `pos`.
+fn pan_visible_to(view_box: &Mutable<ViewBox>, pos: SvgPoint) {
+    let mut view_box = view_box.lock_mut();
+    let visible_offset = view_box.visible_rect().top_left() - view_box.top_left();
+    view_box.set_top_left(pos - visible_offset);
+}
+
+/// Ends the drag, if `pointer_id` is dragging.
+fn release_pointer(drag: &Mutable<Option<Drag>>, pointer_id: i32) {
+    let mut drag = drag.lock_mut();
+    if matches!(*drag, Some(Drag { pointer_id: id, .. }) if id == pointer_id) {
+        info!(
+            "Pointer {} stops dragging the view in the minimap",
+            pointer_id
+        );
+        *drag = None;
+    }
+}
+
+/// Maps `pos` to the user space of the minimap and clamps it to the content
+/// box, so a captured pointer beyond the minimap drags no further than its
+/// edge. Returns the point of the canvas of `view_box`, where the content is
+/// shown rotated.
+fn to_canvas(
+    element: &SvgElement,
+    view_box: &Mutable<ViewBox>,
+    pos: ViewPortPos,
+) -> Option<SvgPoint> {
+    let pos = to_svg(element, pos)?;
+    let view_box = view_box.lock_ref();
+    let pos = view_box.content_box().clamp_point(pos);
+    Some(view_box.content_to_canvas().apply(pos))
+}
+
+/// Maps `pos` to the user space of the minimap, which is the content's.
+fn to_svg(element: &SvgElement, pos: ViewPortPos) -> Option<SvgPoint> {
+    // the minimap is an `svg` element
+    let svg_to_view_port = screen_ctm(element.unchecked_ref::<SvgGraphicsElement>());
+    match svg_to_view_port.and_then(|svg_to_view_port| svg_to_view_port.try_inverse()) {
+        Some(view_port_to_svg) => Some(view_port_to_svg.apply(pos)),
+        None => {
+            warn!("Cannot map the pointer into the minimap, it may have no size or be hidden");
+            None
+        }
+    }
+}
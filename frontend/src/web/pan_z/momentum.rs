@@ -0,0 +1,283 @@
+use std::collections::VecDeque;
+
+use num_traits::Zero;
+use zoon::*;
+
+use crate::web::pan_z::screen_geom::PositionedExtent;
+use crate::web::pan_z::state::PanZoomState;
+use crate::{PanZoomHandle, ScreenVec, ViewPortPos};
+
+/// Only samples this recent are used to compute the release velocity
+const VELOCITY_WINDOW_MS: f64 = 100.0;
+/// If the last sample is older than this on release, the pointer was held
+/// still and there is no momentum.
+const MAX_RELEASE_DELAY_MS: f64 = 50.0;
+
+/// Physical parameters of the kinetic movement after a gesture is released.
+///
+/// The moved "object" decelerates by viscous friction, i.e. its velocity
+/// decays exponentially with rate `friction / mass`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MomentumConfig {
+    /// Inertia of the moved "object". A larger mass keeps moving longer, but
+    /// an impulse -- like a wheel tick -- accelerates it less.
+    pub mass: f64,
+    /// Per millisecond.
+    pub friction: f64,
+    /// In view port units per millisecond. Panning stops below this velocity.
+    pub min_pan_velocity: f64,
+    /// In natural logarithm of the scale ratio per millisecond. Zooming stops
+    /// below this velocity.
+    pub min_zoom_velocity: f64,
+}
+
+impl Default for MomentumConfig {
+    fn default() -> Self {
+        Self {
+            mass: 1.0,
+            friction: 0.005,
+            min_pan_velocity: 0.01,
+            min_zoom_velocity: 0.00001,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Sample {
+    time: f64,
+    pan: ScreenVec,
+    /// natural logarithm of the scale ratio
+    zoom: f64,
+}
+
+/// Records recent pan and zoom deltas with timestamps to compute the velocity
+/// of a gesture.
+#[derive(Debug, Clone, Default)]
+struct VelocityTracker {
+    samples: VecDeque<Sample>,
+}
+
+impl VelocityTracker {
+    fn record(&mut self, sample: Sample) {
+        while let Some(oldest) = self.samples.front() {
+            if sample.time - oldest.time > VELOCITY_WINDOW_MS {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Returns the pan velocity in view port units per millisecond and the
+    /// zoom velocity in natural logarithm of the scale ratio per millisecond.
+    fn velocity(&self, now: f64) -> (ScreenVec, f64) {
+        let (oldest, newest) = match (self.samples.front(), self.samples.back()) {
+            (Some(oldest), Some(newest)) => (oldest, newest),
+            _ => return (ScreenVec::zero(), 0.0),
+        };
+        let duration = newest.time - oldest.time;
+        if now - newest.time > MAX_RELEASE_DELAY_MS || duration <= 0.0 {
+            return (ScreenVec::zero(), 0.0);
+        }
+        // the oldest sample's delta happened before the measured duration
+        let (pan, zoom) = self
+            .samples
+            .iter()
+            .skip(1)
+            .fold((ScreenVec::zero(), 0.0), |(pan, zoom), sample| {
+                (pan + sample.pan, zoom + sample.zoom)
+            });
+        (pan * (1.0 / duration), zoom / duration)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MomentumState {
+    tracker: VelocityTracker,
+    pan_velocity: ScreenVec,
+    zoom_velocity: f64,
+    fix_point: ViewPortPos,
+    /// Incremented on every start and stop of the animation, so that
+    /// animation frames of a stopped animation know to end.
+    generation: u64,
+    running: bool,
+}
+
+impl Default for MomentumState {
+    fn default() -> Self {
+        Self {
+            tracker: VelocityTracker::default(),
+            pan_velocity: ScreenVec::zero(),
+            zoom_velocity: 0.0,
+            fix_point: ViewPortPos::origin(),
+            generation: 0,
+            running: false,
+        }
+    }
+}
+
+/// Keeps panning and zooming the state after a gesture is released, frame by
+/// frame, until friction slows it below a threshold or new input arrives.
+pub(super) struct Momentum<A, PZ: 'static> {
+    handle: PanZoomHandle<A, PZ>,
+    config: MomentumConfig,
+    momentum_state: Mutable<MomentumState>,
+}
+
+impl<A: Clone, PZ> Clone for Momentum<A, PZ> {
+    fn clone(&self) -> Self {
+        Self {
+            handle: self.handle.clone(),
+            config: self.config,
+            momentum_state: self.momentum_state.clone(),
+        }
+    }
+}
+
+impl<A: Clone + PositionedExtent + 'static, PZ: PanZoomState<A>> Momentum<A, PZ> {
+    pub fn new(handle: PanZoomHandle<A, PZ>, config: MomentumConfig) -> Self {
+        Self {
+            handle,
+            config,
+            momentum_state: Mutable::new(MomentumState::default()),
+        }
+    }
+
+    /// Stops the animation and forgets the recorded samples, e.g. because new
+    /// input arrived.
+    pub fn stop(&self) {
+        let mut momentum_state = self.momentum_state.lock_mut();
+        if momentum_state.running {
+            info!("Stopping momentum");
+        }
+        momentum_state.tracker = VelocityTracker::default();
+        momentum_state.pan_velocity = ScreenVec::zero();
+        momentum_state.zoom_velocity = 0.0;
+        momentum_state.running = false;
+        momentum_state.generation += 1;
+    }
+
+    pub fn record_pan(&self, delta: ScreenVec) {
+        self.record(Sample {
+            time: now(),
+            pan: delta,
+            zoom: 0.0,
+        })
+    }
+
+    pub fn record_zoom(&self, scale_ratio: f64, fix_point: ViewPortPos) {
+        self.momentum_state.lock_mut().fix_point = fix_point;
+        self.record(Sample {
+            time: now(),
+            pan: ScreenVec::zero(),
+            zoom: scale_ratio.ln(),
+        })
+    }
+
+    fn record(&self, sample: Sample) {
+        self.momentum_state.lock_mut().tracker.record(sample)
+    }
+
+    /// Starts the animation with the velocity of the recorded samples.
+    pub fn release(&self) {
+        let (pan_velocity, zoom_velocity) = self.momentum_state.lock_ref().tracker.velocity(now());
+        info!(
+            "Releasing with pan velocity {} and zoom velocity {}",
+            pan_velocity, zoom_velocity
+        );
+        {
+            let mut momentum_state = self.momentum_state.lock_mut();
+            momentum_state.tracker = VelocityTracker::default();
+            momentum_state.pan_velocity = pan_velocity;
+            momentum_state.zoom_velocity = zoom_velocity;
+        }
+        self.start();
+    }
+
+    /// Accelerates zooming around `fix_point` by an impulse -- e.g. a wheel
+    /// tick. Without further impulses, the scale changes by `scale_ratio` in
+    /// total, independent of the mass.
+    pub fn push_zoom_impulse(&self, scale_ratio: f64, fix_point: ViewPortPos) {
+        {
+            let mut momentum_state = self.momentum_state.lock_mut();
+            if momentum_state.fix_point != fix_point {
+                // frames in flight must not check the old fix point
+                momentum_state.generation += 1;
+                momentum_state.running = false;
+            }
+            momentum_state.fix_point = fix_point;
+            momentum_state.pan_velocity = ScreenVec::zero();
+            momentum_state.zoom_velocity +=
+                scale_ratio.ln() * self.config.friction / self.config.mass;
+        }
+        self.start();
+    }
+
+    fn start(&self) {
+        let generation = {
+            let mut momentum_state = self.momentum_state.lock_mut();
+            if momentum_state.running {
+                return;
+            }
+            momentum_state.running = true;
+            momentum_state.generation
+        };
+        self.request_frame(generation, None);
+    }
+
+    fn request_frame(&self, generation: u64, last_time: Option<f64>) {
+        let this = self.clone();
+        super::on_next_animation_frame(move |time| this.frame(generation, last_time, time));
+    }
+
+    fn frame(&self, generation: u64, last_time: Option<f64>, time: f64) {
+        let mut momentum_state = self.momentum_state.lock_mut();
+        if momentum_state.generation != generation {
+            return;
+        }
+        let dt = last_time.map_or(0.0, |last_time| time - last_time);
+        let pan_velocity = momentum_state.pan_velocity;
+        let zoom_velocity = momentum_state.zoom_velocity;
+        let fix_point = momentum_state.fix_point;
+
+        if dt > 0.0 {
+            if cfg!(debug_assertions) && zoom_velocity != 0.0 && pan_velocity.is_zero() {
+                let momentum_state = self.momentum_state.clone();
+                super::check_fix_point_after_redraw(self.handle.clone(), fix_point, move || {
+                    momentum_state.lock_ref().generation == generation
+                });
+            }
+            if zoom_velocity != 0.0 {
+                self.handle.zoom_by(fix_point, (zoom_velocity * dt).exp());
+            }
+            if !pan_velocity.is_zero() {
+                self.handle.pan_by(pan_velocity * dt);
+            }
+        }
+
+        let decay = (-self.config.friction / self.config.mass * dt).exp();
+        let pan_velocity = pan_velocity * decay;
+        let zoom_velocity = zoom_velocity * decay;
+        let pan_speed = f64::hypot(pan_velocity.x(), pan_velocity.y());
+        if pan_speed < self.config.min_pan_velocity
+            && zoom_velocity.abs() < self.config.min_zoom_velocity
+        {
+            info!("Momentum exhausted");
+            momentum_state.pan_velocity = ScreenVec::zero();
+            momentum_state.zoom_velocity = 0.0;
+            momentum_state.running = false;
+            return;
+        }
+        momentum_state.pan_velocity = pan_velocity;
+        momentum_state.zoom_velocity = zoom_velocity;
+        drop(momentum_state);
+        self.request_frame(generation, Some(time));
+    }
+}
+
+/// Milliseconds since document creation, same as the time passed to
+/// `request_animation_frame` callbacks.
+fn now() -> f64 {
+    window().performance().unwrap().now()
+}
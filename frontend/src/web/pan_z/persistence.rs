@@ -0,0 +1,156 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use web_sys::Storage;
+use zoon::*;
+
+use crate::{ElementTransform, ViewBox, ViewState};
+
+/// Prefixes the per-viewer keys, so they do not collide with other entries of
+/// the storage.
+const KEY_PREFIX: &str = "pan_z:";
+
+/// Where `persist_view` writes the state.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StorageKind {
+    /// `localStorage`, which survives closing the browser.
+    Local,
+    /// `sessionStorage`, which survives reloads of the tab only.
+    Session,
+}
+
+impl StorageKind {
+    fn storage(&self) -> Option<Storage> {
+        let storage = match self {
+            StorageKind::Local => window().local_storage(),
+            StorageKind::Session => window().session_storage(),
+        };
+        match storage {
+            Ok(Some(storage)) => Some(storage),
+            _ => {
+                warn!("{:?} storage is not available", self);
+                None
+            }
+        }
+    }
+}
+
+/// States, that can be checked before they are restored.
+pub trait ValidateRestored: Sized {
+    /// Returns the restored state adapted to `current` -- e.g. its content
+    /// box and limits -- or `None`, if it cannot be shown.
+    fn validate_restored(self, current: &Self) -> Option<Self>;
+}
+
+/// Keeps the view box and rotation, if they show at least part of the current
+/// content. The content box, limits and `PreserveAspectRatio` are `current`'s,
+/// as the content and its presentation may have changed since the view was
+/// saved.
+///
+/// Deserializing has already rejected an invalid view box.
+impl ValidateRestored for ViewBox {
+    fn validate_restored(self, current: &Self) -> Option<Self> {
+        let view_box = self.view_box();
+        let mut restored = ViewBox::new(view_box, current.content_box());
+        restored.set_rotation(self.rotation());
+        // merely touching the content shows none of it -- unless the content has
+        // no area either
+        let content_bounds = restored.content_bounds();
+        let shown_content = view_box
+            .intersection(&content_bounds)
+            .filter(|shown_content| !shown_content.is_empty() || content_bounds.is_empty());
+        if shown_content.is_none() {
+            warn!(
+                "Discarding restored {}, which does not show the content {}",
+                self,
+                current.content_box()
+            );
+            return None;
+        }
+        restored.set_preserve_aspect_ratio(current.preserve_aspect_ratio());
+        restored.set_limits(current.limits());
+        Some(restored)
+    }
+}
+
+/// Keeps position, scale and rotation, which deserializing has already
+/// validated. The limits are `current`'s. The pan bounds are enforced with the
+/// next change of position, as they depend on the element.
+impl ValidateRestored for ViewState {
+    fn validate_restored(self, current: &Self) -> Option<Self> {
+        let mut restored = self;
+        restored.set_limits_unshown(current.limits());
+        Some(restored)
+    }
+}
+
+/// Keeps translation, scale and rotation, which deserializing has already
+/// validated. The limits are `current`'s.
+impl ValidateRestored for ElementTransform {
+    fn validate_restored(self, current: &Self) -> Option<Self> {
+        let mut restored = self;
+        restored.set_limits_unshown(current.limits());
+        Some(restored)
+    }
+}
+
+/// Returns the state saved by `persist_view` under `key`, validated against
+/// `current` -- or `None`, if there is none or it is invalid.
+pub fn restore_view<PZ: DeserializeOwned + ValidateRestored>(
+    storage: StorageKind,
+    key: &str,
+    current: &PZ,
+) -> Option<PZ> {
+    let key = format!("{}{}", KEY_PREFIX, key);
+    let json = storage.storage()?.get_item(&key).ok()??;
+    match serde_json::from_str::<PZ>(&json) {
+        Ok(restored) => {
+            info!("Restoring view {} from {:?} storage", key, storage);
+            restored.validate_restored(current)
+        }
+        Err(err) => {
+            warn!("Discarding unreadable view {}: {}", key, err);
+            None
+        }
+    }
+}
+
+/// Writes `state` to `storage` under `key` -- which must be unique per viewer
+/// -- whenever it has not changed for `debounce_ms` milliseconds, so that a
+/// gesture is written once, not with every frame.
+///
+/// Changes are no longer written, when `el` is removed from the DOM. Restore
+/// the state with `restore_view` before creating `el`.
+pub fn persist_view<E: RawEl, PZ: Serialize + Clone + 'static>(
+    el: E,
+    state: Mutable<PZ>,
+    storage: StorageKind,
+    key: &str,
+    debounce_ms: u32,
+) -> E {
+    let key = format!("{}{}", KEY_PREFIX, key);
+    el.update_dom_builder(move |builder| {
+        builder.future(super::for_each_settled(
+            state.signal_cloned(),
+            debounce_ms,
+            move |value| write(storage, &key, &value),
+        ))
+    })
+}
+
+fn write<PZ: Serialize>(storage: StorageKind, key: &str, state: &PZ) {
+    let json = match serde_json::to_string(state) {
+        Ok(json) => json,
+        Err(err) => {
+            error!("Cannot serialize view {}: {}", key, err);
+            return;
+        }
+    };
+    if let Some(web_storage) = storage.storage() {
+        if let Err(err) = web_storage.set_item(key, &json) {
+            warn!(
+                "Cannot write view {} to {:?} storage: {:?}",
+                key, storage, err
+            );
+        }
+    }
+}
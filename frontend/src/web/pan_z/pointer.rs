@@ -0,0 +1,242 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
+
+use zoon::events_extra::{PointerCancel, PointerDown, PointerMove, PointerUp};
+use zoon::*;
+
+use crate::web::pan_z::events::LostPointerCapture;
+use crate::web::pan_z::momentum::Momentum;
+use crate::web::pan_z::screen_geom::{Positioned, PositionedExtent};
+use crate::web::pan_z::state::PanZoomState;
+use crate::{PanZoomHandle, ScreenVec, ViewPortPos};
+
+/// The pointers, that currently capture the zoom element, by `pointerId`.
+///
+/// A single pointer drags, two or more pointers pinch and twist: The distance
+/// of the pointers to their centroid determines the scale, their angle around
+/// it the rotation. The centroid is the fix point of both. Moving the centroid
+/// pans.
+#[derive(Debug, Clone, Default)]
+struct Gesture {
+    pointers: BTreeMap<i32, ViewPortPos>,
+}
+
+impl Gesture {
+    fn centroid(&self) -> ViewPortPos {
+        let count = self.pointers.len() as f64;
+        let sum = self
+            .pointers
+            .values()
+            .fold(ScreenVec::new(0.0, 0.0), |sum, pos| sum + pos.as_vec());
+        ViewPortPos::origin() + sum * (1.0 / count)
+    }
+
+    /// Mean distance of the pointers to their centroid. Zero for less than
+    /// two pointers.
+    fn spread(&self) -> f64 {
+        let centroid = self.centroid();
+        let count = self.pointers.len() as f64;
+        self.pointers
+            .values()
+            .map(|pos| {
+                let offset = *pos - centroid;
+                f64::hypot(offset.x(), offset.y())
+            })
+            .sum::<f64>()
+            / count
+    }
+
+    /// Returns the rotation in radians, that best maps the pointers of `old`
+    /// onto those of `self`, each relative to its centroid. Zero for less than
+    /// two pointers.
+    fn twist(&self, old: &Gesture) -> f64 {
+        if self.pointers.len() < 2 || old.pointers.len() < 2 {
+            return 0.0;
+        }
+        let old_centroid = old.centroid();
+        let new_centroid = self.centroid();
+        // sums of the cross and dot products of each pointer's old and new offset
+        let (cross, dot) = self
+            .pointers
+            .iter()
+            .filter_map(|(pointer_id, new_pos)| {
+                let old_pos = old.pointers.get(pointer_id)?;
+                Some((*old_pos - old_centroid, *new_pos - new_centroid))
+            })
+            .fold((0.0, 0.0), |(cross, dot), (old_offset, new_offset)| {
+                (
+                    cross + old_offset.x() * new_offset.y() - old_offset.y() * new_offset.x(),
+                    dot + old_offset.x() * new_offset.x() + old_offset.y() * new_offset.y(),
+                )
+            });
+        f64::atan2(cross, dot)
+    }
+}
+
+/// Pans `state` by dragging the element with any pointer -- mouse, pen or
+/// touch -- and additionally zooms by pinching and rotates by twisting with two
+/// or more touch pointers. Twisting is ignored by states, that cannot rotate.
+///
+/// The pointers are captured by `set_capture_drag_listeners`. Lifting the last
+/// pointer hands the gesture's velocity over to `momentum`.
+pub(super) fn set_gesture_event_listener<
+    A: Clone + PositionedExtent + AsRef<web_sys::Element> + 'static,
+    PZ: PanZoomState<A>,
+>(
+    builder: DomBuilder<A>,
+    handle: PanZoomHandle<A, PZ>,
+    momentum: Momentum<A, PZ>,
+) -> DomBuilder<A> {
+    let gesture: Mutable<Gesture> = Mutable::new(Gesture::default());
+
+    let on_down = {
+        let gesture = gesture.clone();
+        let momentum = momentum.clone();
+        move |pointer_id: i32, pos: ViewPortPos| {
+            momentum.stop();
+            info!("Pointer {} joins gesture at {}", pointer_id, pos);
+            gesture.lock_mut().pointers.insert(pointer_id, pos);
+            true
+        }
+    };
+
+    let on_move = {
+        let gesture = gesture.clone();
+        let momentum = momentum.clone();
+        move |pointer_id: i32, pos: ViewPortPos| {
+            let mut gesture = gesture.lock_mut();
+            let old_gesture = gesture.clone();
+            let old_centroid = gesture.centroid();
+            let old_spread = gesture.spread();
+            gesture.pointers.insert(pointer_id, pos);
+            let new_centroid = gesture.centroid();
+            let new_spread = gesture.spread();
+
+            let scale_ratio =
+                (old_spread > 0.0 && new_spread > 0.0).then(|| new_spread / old_spread);
+            let twist = if handle.can_rotate() {
+                gesture.twist(&old_gesture)
+            } else {
+                0.0
+            };
+            let pan_delta = new_centroid - old_centroid;
+
+            // The element's geometry in the DOM is updated only in the next animation
+            // frame. Thus zoom, rotate and pan at once, around the centroid the DOM still
+            // knows about.
+            handle.transform_by(old_centroid, pan_delta, scale_ratio.unwrap_or(1.0), twist);
+            if let Some(scale_ratio) = scale_ratio {
+                momentum.record_zoom(scale_ratio, new_centroid);
+            }
+            momentum.record_pan(pan_delta);
+        }
+    };
+
+    let on_end = move |pointer_id: i32, end: DragEnd| {
+        if release_pointer(&gesture, pointer_id) {
+            match end {
+                DragEnd::Released => momentum.release(),
+                // A cancelled gesture has not been released intentionally. It must not
+                // go on moving.
+                DragEnd::Cancelled => momentum.stop(),
+            }
+        }
+    };
+
+    set_capture_drag_listeners(builder, on_down, on_move, on_end)
+}
+
+/// Removes the pointer `pointer_id` from the gesture. Returns `true`, if it was
+/// the last pointer of the gesture.
+fn release_pointer(gesture: &Mutable<Gesture>, pointer_id: i32) -> bool {
+    let mut gesture = gesture.lock_mut();
+    if gesture.pointers.remove(&pointer_id).is_some() {
+        info!("Pointer {} leaves gesture", pointer_id);
+        gesture.pointers.is_empty()
+    } else {
+        false
+    }
+}
+
+/// How a pointer stopped dragging
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum DragEnd {
+    /// By `pointerup`
+    Released,
+    /// By `pointercancel`, by losing the capture without `pointerup` or as it
+    /// could not be captured at all
+    Cancelled,
+}
+
+/// Drags with pointers, that are captured on `pointerdown`, so a drag goes on,
+/// even if the pointer leaves the element.
+///
+/// `on_down` decides, whether a pointer starts dragging; only then it is
+/// captured. `on_move` gets the moves of the dragging pointers only. `on_end`
+/// is called once per dragging pointer, on `pointerup`, `pointercancel` or
+/// `lostpointercapture` -- or if it cannot be captured.
+pub(super) fn set_capture_drag_listeners<A: AsRef<web_sys::Element> + Clone + 'static>(
+    builder: DomBuilder<A>,
+    mut on_down: impl FnMut(i32, ViewPortPos) -> bool + 'static,
+    mut on_move: impl FnMut(i32, ViewPortPos) + 'static,
+    on_end: impl FnMut(i32, DragEnd) + 'static,
+) -> DomBuilder<A> {
+    let element = builder.__internal_element();
+    let dragging: Mutable<BTreeSet<i32>> = Mutable::new(BTreeSet::new());
+    let on_end = RefCell::new(on_end);
+    // `pointerup` and `pointercancel` are followed by `lostpointercapture`, so
+    // only the first of them ends the drag.
+    let end = Rc::new({
+        let dragging = dragging.clone();
+        move |pointer_id: i32, end: DragEnd| {
+            let was_dragging = dragging.lock_mut().remove(&pointer_id);
+            if was_dragging {
+                (on_end.borrow_mut())(pointer_id, end)
+            }
+        }
+    });
+
+    let on_pointer_down = {
+        let dragging = dragging.clone();
+        let end = end.clone();
+        move |e: PointerDown| {
+            let pointer_id = e.pointer_id();
+            if !on_down(pointer_id, e.pos()) {
+                return;
+            }
+            dragging.lock_mut().insert(pointer_id);
+            if let Err(err) = element.as_ref().set_pointer_capture(pointer_id) {
+                error!("Could not capture pointer {}: {:?}", pointer_id, err);
+                end(pointer_id, DragEnd::Cancelled);
+            }
+        }
+    };
+    let on_pointer_move = move |e: PointerMove| {
+        let pointer_id = e.pointer_id();
+        let is_dragging = dragging.lock_ref().contains(&pointer_id);
+        if is_dragging {
+            on_move(pointer_id, e.pos())
+        }
+    };
+    let on_pointer_up = {
+        let end = end.clone();
+        move |e: PointerUp| end(e.pointer_id(), DragEnd::Released)
+    };
+    let on_pointer_cancel = {
+        let end = end.clone();
+        move |e: PointerCancel| end(e.pointer_id(), DragEnd::Cancelled)
+    };
+    let on_lost_pointer_capture =
+        move |e: LostPointerCapture| end(e.pointer_id(), DragEnd::Cancelled);
+
+    builder
+        // the browser must neither scroll nor zoom the page, while dragging with touch
+        // pointers
+        .style("touch-action", "none")
+        .event(on_pointer_down)
+        .event(on_pointer_move)
+        .event(on_pointer_up)
+        .event(on_pointer_cancel)
+        .event(on_lost_pointer_capture)
+}
@@ -0,0 +1,77 @@
+use js_sys::Array;
+use wasm_bindgen::JsCast;
+use web_sys::{ResizeObserver, ResizeObserverEntry, SvgElement};
+use zoon::*;
+
+use crate::{ScreenVec, ViewBox};
+
+/// The size of a view port, that is kept in sync with its view box by
+/// `sync_view_box_with_view_port`.
+#[derive(Debug, Clone, Default)]
+pub struct ViewPortSync {
+    view_port_size: Mutable<Option<ScreenVec>>,
+}
+
+impl ViewPortSync {
+    /// The current size of the view port in view port units -- `None`, until
+    /// it has been laid out.
+    pub fn view_port_size(&self) -> impl Signal<Item = Option<ScreenVec>> {
+        self.view_port_size.signal()
+    }
+}
+
+/// Keeps the aspect ratio of `view_box` in sync with the view port, i.e. the
+/// size of `el` -- which should be created from a signal of `view_box` by
+/// `IntoElementWithAttributeSignal`.
+///
+/// On every change of the size of `el`, the view box keeps its center and
+/// scale, but changes its shape to the view port's. Otherwise empty borders
+/// would appear, when zooming in. The returned `ViewPortSync` signals the
+/// current size of the view port.
+///
+/// The observer is disconnected, when `el` is removed from the DOM. Browsers
+/// without `ResizeObserver` do not sync, the size of the view port stays
+/// `None` then.
+pub fn sync_view_box_with_view_port(
+    el: RawSvgEl,
+    view_box: Mutable<ViewBox>,
+) -> (ViewPortSync, RawSvgEl) {
+    let sync = ViewPortSync::default();
+    let view_port_size = sync.view_port_size.clone();
+    let el = el.update_dom_builder(|builder| {
+        let builder: DomBuilder<SvgElement> = builder;
+        let element = builder.__internal_element();
+
+        let on_resize = Closure::wrap(Box::new(move |entries: Array| {
+            // only `element` is observed
+            let entry: ResizeObserverEntry = match entries.get(0).dyn_into() {
+                Ok(entry) => entry,
+                Err(_) => return,
+            };
+            let content_rect = entry.content_rect();
+            let (width, height) = (content_rect.width(), content_rect.height());
+            info!("View port resized to {} ⨯ {}", width, height);
+            view_port_size.set(Some(ScreenVec::new(width, height)));
+            if width > 0.0 && height > 0.0 {
+                view_box.lock_mut().set_aspect_ratio(width / height);
+            }
+        }) as Box<dyn FnMut(Array)>);
+        let observer = match ResizeObserver::new(on_resize.as_ref().unchecked_ref()) {
+            Ok(observer) => observer,
+            Err(err) => {
+                warn!(
+                    "Cannot observe the size of the view port, not syncing the view box: {:?}",
+                    err
+                );
+                return builder;
+            }
+        };
+        observer.observe(&element);
+
+        builder.after_removed(move |_| {
+            observer.disconnect();
+            drop(on_resize);
+        })
+    });
+    (sync, el)
+}
@@ -1,10 +1,13 @@
-use crate::svg::SvgPoint;
-use num_traits::Zero;
+use crate::geom::{ElementLocal, Page, Point, Rect, Svg, Transform, Vector, ViewPort};
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
-use std::ops::{Add, AddAssign, Mul, Sub};
+use std::ops::Mul;
+use std::str::FromStr;
 use wasm_bindgen::JsCast;
-use web_sys::{Document, EventTarget, PointerEvent, SvgsvgElement, Window};
+use web_sys::{
+    Document, DomMatrix, EventTarget, PointerEvent, SvgGraphicsElement, SvgMatrix, SvgsvgElement,
+    Window,
+};
 use zoon::events_extra::{
     PointerCancel, PointerDown, PointerLeave, PointerMove, PointerUp, WheelEvent,
 };
@@ -19,6 +22,14 @@ use zoon::events_extra::{
 /// * https://developer.mozilla.org/en-US/docs/Web/API/SVGMatrix
 /// * https://en.wikipedia.org/wiki/Transformation_matrix#Affine_transformations
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialization::MatrixV1",
+        try_from = "crate::serialization::MatrixV1"
+    )
+)]
 pub struct AffineTransformMatrix {
     a: f64,
     b: f64,
@@ -28,20 +39,6 @@ pub struct AffineTransformMatrix {
     f: f64,
 }
 
-impl From<&SvgsvgElement> for AffineTransformMatrix {
-    fn from(element: &SvgsvgElement) -> Self {
-        let svg_matrix = element.get_screen_ctm().unwrap();
-        Self {
-            a: svg_matrix.a() as f64,
-            b: svg_matrix.b() as f64,
-            c: svg_matrix.c() as f64,
-            d: svg_matrix.d() as f64,
-            e: svg_matrix.e() as f64,
-            f: svg_matrix.f() as f64,
-        }
-    }
-}
-
 impl AffineTransformMatrix {
     ///
     /// # See
@@ -71,253 +68,297 @@ impl AffineTransformMatrix {
         );
         Some(i)
     }
-}
 
-/// A 2-dimensional position relative to the view port in screen coordinates --
-/// i.e. the y-axis points downwards.
-///
-/// ViewPortPos are assumed to always be relative to the top-left corner of the
-/// view port, i.e. the `ViewPortPos::origin` always refers to the top-left
-/// corner of the view port.
-///
-/// ViewPortPos can be off screen and outside the view port, i.e. their
-/// positions can be negative or beyond the displayable area.
-///
-/// # See
-/// https://developer.mozilla.org/en-US/docs/Web/CSS/Viewport_concepts
-#[derive(Debug, Copy, Clone, Default, PartialEq)]
-pub struct ViewPortPos {
-    x: f64,
-    y: f64,
-}
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Self { a, b, c, d, e, f }
+    }
 
-impl Display for ViewPortPos {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
     }
-}
 
-impl Add<ScreenVec> for ViewPortPos {
-    type Output = ViewPortPos;
+    pub fn translation(tx: f64, ty: f64) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
 
-    fn add(self, rhs: ScreenVec) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
+    pub fn scaling(sx: f64, sy: f64) -> Self {
+        Self::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
     }
-}
 
-impl AddAssign<ScreenVec> for ViewPortPos {
-    fn add_assign(&mut self, rhs: ScreenVec) {
-        self.x += rhs.x;
-        self.y += rhs.y;
+    /// Rotates by `angle` radians -- clockwise on screen, as the y-axis points
+    /// downwards.
+    pub fn rotation(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
     }
-}
 
-impl Sub for ViewPortPos {
-    type Output = ScreenVec;
+    /// Skews along the x-axis by `angle` radians, like SVG's `skewX`.
+    pub fn skew_x(angle: f64) -> Self {
+        Self::new(1.0, 0.0, angle.tan(), 1.0, 0.0, 0.0)
+    }
 
-    fn sub(self, rhs: Self) -> Self::Output {
-        ScreenVec {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
-        }
+    /// Skews along the y-axis by `angle` radians, like SVG's `skewY`.
+    pub fn skew_y(angle: f64) -> Self {
+        Self::new(1.0, angle.tan(), 0.0, 1.0, 0.0, 0.0)
     }
-}
 
-impl ViewPortPos {
-    pub fn new(x: f64, y: f64) -> Self {
-        Self { x, y }
+    pub fn a(&self) -> f64 {
+        self.a
     }
-    pub fn origin() -> Self {
-        Self { x: 0.0, y: 0.0 }
+    pub fn b(&self) -> f64 {
+        self.b
     }
-    pub fn x(&self) -> f64 {
-        self.x
+    pub fn c(&self) -> f64 {
+        self.c
     }
-    pub fn y(&self) -> f64 {
-        self.y
+    pub fn d(&self) -> f64 {
+        self.d
     }
-    pub fn as_vec(&self) -> ScreenVec {
-        ScreenVec {
-            x: self.x,
-            y: self.y,
-        }
+    pub fn e(&self) -> f64 {
+        self.e
     }
-    pub fn to_svg_coords(
-        &self,
-        view_port_to_svg_transformation: AffineTransformMatrix,
-    ) -> SvgPoint {
-        let m = view_port_to_svg_transformation;
-        SvgPoint::new(
-            m.a * self.x + m.c * self.y + m.e,
-            m.b * self.x + m.d * self.y + m.f,
-        )
+    pub fn f(&self) -> f64 {
+        self.f
     }
-    pub fn from_svg_coords(
-        svg_point: SvgPoint,
-        svg_to_view_port_transformation: AffineTransformMatrix,
-    ) -> Self {
-        let m = svg_to_view_port_transformation;
-        Self::new(
-            m.a * svg_point.x + m.c * svg_point.y + m.e,
-            m.b * svg_point.x + m.d * svg_point.y + m.f,
+
+    pub fn determinant(&self) -> f64 {
+        self.a * self.d - self.c * self.b
+    }
+
+    /// Returns the transformed point `(x, y)`.
+    pub fn apply_to_point(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
         )
     }
-}
 
-/// A 2-dimensional vector in screen coordinates -- i.e. the y-axis points
-/// downwards
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub struct ScreenVec {
-    x: f64,
-    y: f64,
-}
-impl Display for ScreenVec {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Vec({}, {})", self.x, self.y)
+    /// Returns the transformed vector `(x, y)`. Unlike points, vectors are not
+    /// affected by the translation part of the transformation.
+    pub fn apply_to_vector(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.a * x + self.c * y, self.b * x + self.d * y)
     }
-}
-impl Mul<f64> for ScreenVec {
-    type Output = Self;
 
-    fn mul(self, rhs: f64) -> Self::Output {
-        Self {
-            x: self.x * rhs,
-            y: self.y * rhs,
+    /// Splits the transformation into
+    /// `translate(x y) rotate(rotation) skewX(skew_x) scale(scale_x scale_y)`,
+    /// applied from right to left. Returns `None`, if the matrix is singular
+    /// or not finite.
+    ///
+    /// # See
+    /// https://en.wikipedia.org/wiki/QR_decomposition
+    pub fn try_decompose(&self) -> Option<Decomposition> {
+        let det = self.determinant();
+        // any absolute threshold would reject tiny, but invertible scales
+        if det == 0.0 || !det.is_finite() {
+            return None;
         }
+        // The first column is the rotated x-axis scaled by scale_x
+        let scale_x = f64::hypot(self.a, self.b);
+        let rotation = f64::atan2(self.b, self.a);
+        // The second column, rotated back, is (scale_y * tan(skew_x), scale_y)
+        let (sin, cos) = rotation.sin_cos();
+        let skewed = self.c * cos + self.d * sin;
+        let scale_y = det / scale_x;
+        Some(Decomposition {
+            translate_x: self.e,
+            translate_y: self.f,
+            rotation,
+            skew_x: (skewed / scale_y).atan(),
+            scale_x,
+            scale_y,
+        })
+    }
+
+    /// Returns `matrix(a, b, c, d, e, f)` for the CSS `transform` property.
+    pub fn to_css_string(&self) -> String {
+        format!(
+            "matrix({}, {}, {}, {}, {}, {})",
+            self.a, self.b, self.c, self.d, self.e, self.f
+        )
     }
-}
 
-impl Add for ScreenVec {
-    type Output = Self;
+    /// Creates an `SVGMatrix`, which can only be created by an `<svg>`
+    /// element.
+    pub fn to_svg_matrix(&self, svg: &SvgsvgElement) -> SvgMatrix {
+        let svg_matrix = svg.create_svg_matrix();
+        svg_matrix.set_a(self.a as f32);
+        svg_matrix.set_b(self.b as f32);
+        svg_matrix.set_c(self.c as f32);
+        svg_matrix.set_d(self.d as f32);
+        svg_matrix.set_e(self.e as f32);
+        svg_matrix.set_f(self.f as f32);
+        svg_matrix
+    }
 
-    fn add(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
+    pub fn to_dom_matrix(&self) -> DomMatrix {
+        let dom_matrix = DomMatrix::new().unwrap();
+        dom_matrix.set_a(self.a);
+        dom_matrix.set_b(self.b);
+        dom_matrix.set_c(self.c);
+        dom_matrix.set_d(self.d);
+        dom_matrix.set_e(self.e);
+        dom_matrix.set_f(self.f);
+        dom_matrix
     }
 }
 
-impl Sub for ScreenVec {
+/// `self * rhs` applies `rhs` first, then `self` -- as with the matrices of
+/// nested SVG elements, where the child's transform is applied first.
+impl Mul for AffineTransformMatrix {
     type Output = Self;
 
-    fn sub(self, rhs: Self) -> Self::Output {
+    fn mul(self, rhs: Self) -> Self::Output {
         Self {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
+            a: self.a * rhs.a + self.c * rhs.b,
+            b: self.b * rhs.a + self.d * rhs.b,
+            c: self.a * rhs.c + self.c * rhs.d,
+            d: self.b * rhs.c + self.d * rhs.d,
+            e: self.a * rhs.e + self.c * rhs.f + self.e,
+            f: self.b * rhs.e + self.d * rhs.f + self.f,
         }
     }
 }
 
-impl Zero for ScreenVec {
-    fn zero() -> Self {
-        Self { x: 0.0, y: 0.0 }
+impl From<&SvgMatrix> for AffineTransformMatrix {
+    fn from(svg_matrix: &SvgMatrix) -> Self {
+        Self::new(
+            svg_matrix.a() as f64,
+            svg_matrix.b() as f64,
+            svg_matrix.c() as f64,
+            svg_matrix.d() as f64,
+            svg_matrix.e() as f64,
+            svg_matrix.f() as f64,
+        )
     }
+}
 
-    fn is_zero(&self) -> bool {
-        self.x == 0.0 && self.y == 0.0
+/// Only the 2D part of the `DOMMatrix` is taken.
+impl From<&DomMatrix> for AffineTransformMatrix {
+    fn from(dom_matrix: &DomMatrix) -> Self {
+        Self::new(
+            dom_matrix.a(),
+            dom_matrix.b(),
+            dom_matrix.c(),
+            dom_matrix.d(),
+            dom_matrix.e(),
+            dom_matrix.f(),
+        )
     }
 }
 
-impl ScreenVec {
-    pub fn new(x: f64, y: f64) -> Self {
-        Self { x, y }
-    }
-    pub fn x(&self) -> f64 {
-        self.x
-    }
-    pub fn y(&self) -> f64 {
-        self.y
+/// Parses CSS `matrix(a, b, c, d, e, f)` as well as the SVG `transform`
+/// attribute's `matrix(a b c d e f)`.
+impl FromStr for AffineTransformMatrix {
+    type Err = ParseMatrixErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseMatrixErr(s.to_string());
+        let arguments = s
+            .trim()
+            .strip_prefix("matrix(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(err)?;
+        let numbers = arguments
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|number| !number.is_empty())
+            .map(|number| number.parse::<f64>().map_err(|_| err()))
+            .collect::<Result<Vec<f64>, ParseMatrixErr>>()?;
+        match numbers[..] {
+            [a, b, c, d, e, f] => Ok(Self::new(a, b, c, d, e, f)),
+            _ => Err(err()),
+        }
     }
 }
 
-/// A rect relative to the view port in screen-coordinates -- i.e. the y-axis
-/// points downwards.
-///
-/// # See
-/// https://developer.mozilla.org/en-US/docs/Web/CSS/Viewport_concepts
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub struct ViewPortRect {
-    top_left: ViewPortPos,
-    width: f64,
-    height: f64,
-}
+#[derive(Debug, Clone)]
+pub struct ParseMatrixErr(String);
 
-impl Display for ViewPortRect {
+impl Display for ParseMatrixErr {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "Rect x:{{{}..{}}} ⨯ y:{{{}..{}}}",
-            self.left(),
-            self.right(),
-            self.top(),
-            self.bottom()
-        )
+        write!(f, "Expected matrix(a, b, c, d, e, f), got {}", self.0)
     }
 }
 
-impl ViewPortRect {
-    pub fn new(top_left: ViewPortPos, width: f64, height: f64) -> Self {
-        debug_assert!(width >= 0.0);
-        debug_assert!(height >= 0.0);
-        Self {
-            top_left,
-            width,
-            height,
-        }
-    }
+impl Error for ParseMatrixErr {}
 
-    pub fn top(&self) -> f64 {
-        self.top_left.y
-    }
-    pub fn left(&self) -> f64 {
-        self.top_left.x
-    }
-    pub fn bottom(&self) -> f64 {
-        self.top_left.y + self.height
-    }
-    pub fn right(&self) -> f64 {
-        self.top_left.x + self.width
-    }
-    pub fn width(&self) -> f64 {
-        self.width
-    }
-    pub fn height(&self) -> f64 {
-        self.height
-    }
-    pub fn top_left(&self) -> ViewPortPos {
-        self.top_left
-    }
-    pub fn bottom_right(&self) -> ViewPortPos {
-        ViewPortPos::new(self.right(), self.bottom())
+/// The parts of an `AffineTransformMatrix`, see
+/// `AffineTransformMatrix::try_decompose`. Angles are in radians.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialization::DecompositionV1",
+        try_from = "crate::serialization::DecompositionV1"
+    )
+)]
+pub struct Decomposition {
+    pub translate_x: f64,
+    pub translate_y: f64,
+    pub rotation: f64,
+    pub skew_x: f64,
+    pub scale_x: f64,
+    pub scale_y: f64,
+}
+
+impl Decomposition {
+    pub fn compose(&self) -> AffineTransformMatrix {
+        AffineTransformMatrix::translation(self.translate_x, self.translate_y)
+            * AffineTransformMatrix::rotation(self.rotation)
+            * AffineTransformMatrix::skew_x(self.skew_x)
+            * AffineTransformMatrix::scaling(self.scale_x, self.scale_y)
     }
+}
 
-    /// width / height
-    pub fn aspect_ratio(&self) -> f64 {
-        self.width() / self.height()
-    }
+/// A 2-dimensional position relative to the view port in screen coordinates --
+/// i.e. the y-axis points downwards.
+///
+/// ViewPortPos are assumed to always be relative to the top-left corner of the
+/// view port, i.e. the `ViewPortPos::origin` always refers to the top-left
+/// corner of the view port.
+///
+/// ViewPortPos can be off screen and outside the view port, i.e. their
+/// positions can be negative or beyond the displayable area.
+pub type ViewPortPos = Point<ViewPort>;
 
-    /// Returns the vector from the top-left corner of this rect to `point`.
-    pub fn offset(&self, point: ViewPortPos) -> ScreenVec {
-        let offset = point - self.top_left;
-        warn!("offset for {} to top left of {} is {}", point, self, offset);
-        offset
-    }
+/// A 2-dimensional position relative to the top-left corner of the document.
+pub type PagePos = Point<Page>;
 
-    /// Returns a vector with each offset dimension divided by the rect-size in
-    /// the respective dimension.
-    ///
-    /// `point`s inside the rect thus have values in each dimensions between
-    /// `0.0` and `1.0`.
-    pub fn rect_size_relative_offset(&self, point: ViewPortPos) -> ScreenVec {
-        let absolute_offset = self.offset(point);
-        ScreenVec {
-            x: absolute_offset.x / self.width,
-            y: absolute_offset.y / self.height,
-        }
+/// A 2-dimensional position in the local coordinate system of an HTML element,
+/// i.e. relative to the top-left corner of the element without any CSS
+/// transform applied, in CSS pixels.
+pub type ElementPos = Point<ElementLocal>;
+
+/// A 2-dimensional vector in the local coordinate system of an HTML element
+pub type ElementVec = Vector<ElementLocal>;
+
+/// A rect in the local coordinate system of an HTML element
+pub type ElementRect = Rect<ElementLocal>;
+
+/// A 2-dimensional vector in screen coordinates -- i.e. the y-axis points
+/// downwards
+pub type ScreenVec = Vector<ViewPort>;
+
+/// A rect relative to the view port in screen-coordinates -- i.e. the y-axis
+/// points downwards.
+pub type ViewPortRect = Rect<ViewPort>;
+
+/// The screen CTM of an SVG element, i.e. the transformation from the
+/// element's local user space to the view port, including the transforms of
+/// all ancestors -- or `None`, if the element is not rendered, e.g. by
+/// `display: none`.
+pub(crate) fn screen_ctm(element: &SvgGraphicsElement) -> Option<Transform<Svg, ViewPort>> {
+    let matrix = element.get_screen_ctm()?;
+    Some(Transform::new(AffineTransformMatrix::from(&matrix)))
+}
+
+/// Shifts by the scroll position of `window`.
+impl From<&Window> for Transform<ViewPort, Page> {
+    fn from(window: &Window) -> Self {
+        Self::new(AffineTransformMatrix::translation(
+            window.scroll_x().unwrap(),
+            window.scroll_y().unwrap(),
+        ))
     }
 }
 
@@ -327,60 +368,39 @@ pub trait Positioned {
 
 impl Positioned for PointerEvent {
     fn pos(&self) -> ViewPortPos {
-        ViewPortPos {
-            x: self.client_x() as f64,
-            y: self.client_y() as f64,
-        }
+        ViewPortPos::new(self.client_x() as f64, self.client_y() as f64)
     }
 }
 
 impl Positioned for PointerDown {
     fn pos(&self) -> ViewPortPos {
-        ViewPortPos {
-            x: self.x() as f64,
-            y: self.y() as f64,
-        }
+        ViewPortPos::new(self.x() as f64, self.y() as f64)
     }
 }
 impl Positioned for PointerUp {
     fn pos(&self) -> ViewPortPos {
-        ViewPortPos {
-            x: self.x() as f64,
-            y: self.y() as f64,
-        }
+        ViewPortPos::new(self.x() as f64, self.y() as f64)
     }
 }
 impl Positioned for PointerCancel {
     fn pos(&self) -> ViewPortPos {
-        ViewPortPos {
-            x: self.x() as f64,
-            y: self.y() as f64,
-        }
+        ViewPortPos::new(self.x() as f64, self.y() as f64)
     }
 }
 impl Positioned for PointerLeave {
     fn pos(&self) -> ViewPortPos {
-        ViewPortPos {
-            x: self.x() as f64,
-            y: self.y() as f64,
-        }
+        ViewPortPos::new(self.x() as f64, self.y() as f64)
     }
 }
 impl Positioned for PointerMove {
     fn pos(&self) -> ViewPortPos {
-        ViewPortPos {
-            x: self.x() as f64,
-            y: self.y() as f64,
-        }
+        ViewPortPos::new(self.x() as f64, self.y() as f64)
     }
 }
 
 impl Positioned for WheelEvent {
     fn pos(&self) -> ViewPortPos {
-        ViewPortPos {
-            x: self.x() as f64,
-            y: self.y() as f64,
-        }
+        ViewPortPos::new(self.x() as f64, self.y() as f64)
     }
 }
 
@@ -411,7 +431,7 @@ impl PositionedExtent for web_sys::Window {
                 height_js_value
             )
         });
-        ViewPortRect::new(ViewPortPos::origin(), width, height)
+        ViewPortRect::new(ViewPortPos::origin(), ScreenVec::new(width, height))
     }
 }
 
@@ -428,8 +448,7 @@ impl PositionedExtent for web_sys::Document {
         let bc_rect = body.get_bounding_client_rect();
         ViewPortRect::new(
             ViewPortPos::new(bc_rect.x(), bc_rect.y()),
-            bc_rect.width(),
-            bc_rect.height(),
+            ScreenVec::new(bc_rect.width(), bc_rect.height()),
         )
     }
 }
@@ -444,8 +463,7 @@ impl PositionedExtent for web_sys::Element {
         let bc_rect = self.get_bounding_client_rect();
         ViewPortRect::new(
             ViewPortPos::new(bc_rect.x(), bc_rect.y()),
-            bc_rect.width(),
-            bc_rect.height(),
+            ScreenVec::new(bc_rect.width(), bc_rect.height()),
         )
     }
 }
@@ -461,8 +479,7 @@ impl PositionedExtent for web_sys::HtmlElement {
         let bc_rect = self.get_bounding_client_rect();
         ViewPortRect::new(
             ViewPortPos::new(bc_rect.x(), bc_rect.y()),
-            bc_rect.width(),
-            bc_rect.height(),
+            ScreenVec::new(bc_rect.width(), bc_rect.height()),
         )
     }
 }
@@ -477,8 +494,7 @@ impl PositionedExtent for web_sys::SvgElement {
         let bc_rect = self.get_bounding_client_rect();
         ViewPortRect::new(
             ViewPortPos::new(bc_rect.x(), bc_rect.y()),
-            bc_rect.width(),
-            bc_rect.height(),
+            ScreenVec::new(bc_rect.width(), bc_rect.height()),
         )
     }
 }
@@ -493,8 +509,7 @@ impl PositionedExtent for web_sys::SvgsvgElement {
         let bc_rect = self.get_bounding_client_rect();
         ViewPortRect::new(
             ViewPortPos::new(bc_rect.x(), bc_rect.y()),
-            bc_rect.width(),
-            bc_rect.height(),
+            ScreenVec::new(bc_rect.width(), bc_rect.height()),
         )
     }
 }
@@ -608,3 +623,186 @@ impl TryFrom<EventTarget> for PositionedJsObject {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    fn assert_matrix_eq(actual: AffineTransformMatrix, expected: AffineTransformMatrix) {
+        let (a, e) = (actual, expected);
+        assert!(
+            [
+                (a.a, e.a),
+                (a.b, e.b),
+                (a.c, e.c),
+                (a.d, e.d),
+                (a.e, e.e),
+                (a.f, e.f)
+            ]
+            .iter()
+            .all(|(a, e)| abs_diff_eq!(a, e, epsilon = 1e-12)),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    fn assert_pair_eq(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            abs_diff_eq!(actual.0, expected.0, epsilon = 1e-12)
+                && abs_diff_eq!(actual.1, expected.1, epsilon = 1e-12),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn constructors() {
+        let point = (2.0, 3.0);
+        assert_pair_eq(
+            AffineTransformMatrix::identity().apply_to_point(2.0, 3.0),
+            point,
+        );
+        assert_pair_eq(
+            AffineTransformMatrix::translation(10.0, -1.0).apply_to_point(2.0, 3.0),
+            (12.0, 2.0),
+        );
+        assert_pair_eq(
+            AffineTransformMatrix::scaling(2.0, 0.5).apply_to_point(2.0, 3.0),
+            (4.0, 1.5),
+        );
+        // clockwise on screen: the x-axis turns into the y-axis
+        assert_pair_eq(
+            AffineTransformMatrix::rotation(FRAC_PI_2).apply_to_point(1.0, 0.0),
+            (0.0, 1.0),
+        );
+        assert_pair_eq(
+            AffineTransformMatrix::skew_x(FRAC_PI_4).apply_to_point(0.0, 1.0),
+            (1.0, 1.0),
+        );
+        assert_pair_eq(
+            AffineTransformMatrix::skew_y(FRAC_PI_4).apply_to_point(1.0, 0.0),
+            (1.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn multiplication_applies_the_right_hand_side_first() {
+        let translation = AffineTransformMatrix::translation(10.0, 0.0);
+        let rotation = AffineTransformMatrix::rotation(FRAC_PI_2);
+        assert_pair_eq(
+            (translation * rotation).apply_to_point(1.0, 0.0),
+            (10.0, 1.0),
+        );
+        assert_pair_eq(
+            (rotation * translation).apply_to_point(1.0, 0.0),
+            (0.0, 11.0),
+        );
+        assert_matrix_eq(
+            AffineTransformMatrix::identity() * rotation,
+            rotation * AffineTransformMatrix::identity(),
+        );
+    }
+
+    #[test]
+    fn vectors_ignore_the_translation() {
+        let matrix =
+            AffineTransformMatrix::translation(5.0, 7.0) * AffineTransformMatrix::scaling(2.0, 3.0);
+        assert_pair_eq(matrix.apply_to_point(1.0, 1.0), (7.0, 10.0));
+        assert_pair_eq(matrix.apply_to_vector(1.0, 1.0), (2.0, 3.0));
+    }
+
+    #[test]
+    fn decompose_and_compose_round_trip() {
+        let matrix = AffineTransformMatrix::translation(5.0, -3.0)
+            * AffineTransformMatrix::rotation(0.7)
+            * AffineTransformMatrix::skew_x(0.3)
+            * AffineTransformMatrix::scaling(2.0, 0.5);
+        let decomposition = matrix.try_decompose().unwrap();
+        assert_relative_eq!(decomposition.translate_x, 5.0, epsilon = 1e-12);
+        assert_relative_eq!(decomposition.translate_y, -3.0, epsilon = 1e-12);
+        assert_relative_eq!(decomposition.rotation, 0.7, epsilon = 1e-12);
+        assert_relative_eq!(decomposition.skew_x, 0.3, epsilon = 1e-12);
+        assert_relative_eq!(decomposition.scale_x, 2.0, epsilon = 1e-12);
+        assert_relative_eq!(decomposition.scale_y, 0.5, epsilon = 1e-12);
+        assert_matrix_eq(decomposition.compose(), matrix);
+    }
+
+    #[test]
+    fn decompose_tiny_scales() {
+        let decomposition = AffineTransformMatrix::scaling(1e-9, 1e-9)
+            .try_decompose()
+            .unwrap();
+        assert_relative_eq!(decomposition.scale_x, 1e-9);
+        assert_relative_eq!(decomposition.scale_y, 1e-9);
+    }
+
+    #[test]
+    fn decompose_singular_matrix() {
+        assert_eq!(
+            AffineTransformMatrix::scaling(0.0, 1.0).try_decompose(),
+            None
+        );
+        // the columns are parallel
+        assert_eq!(
+            AffineTransformMatrix::new(1.0, 2.0, 2.0, 4.0, 3.0, 3.0).try_decompose(),
+            None
+        );
+        assert_eq!(
+            AffineTransformMatrix::scaling(f64::INFINITY, 1.0).try_decompose(),
+            None
+        );
+    }
+
+    #[test]
+    fn css_string_round_trip() {
+        for matrix in [
+            AffineTransformMatrix::identity(),
+            AffineTransformMatrix::new(0.1, -2.5e-7, 1e300, -0.0, 123.456, -7.0),
+            AffineTransformMatrix::rotation(0.7) * AffineTransformMatrix::scaling(3.0, 1.0 / 3.0),
+        ] {
+            let parsed: AffineTransformMatrix = matrix.to_css_string().parse().unwrap();
+            assert_eq!(parsed, matrix);
+        }
+    }
+
+    #[test]
+    fn parse_svg_attribute_syntax() {
+        let parsed: AffineTransformMatrix = " matrix(1 2 3 4 5 6) ".parse().unwrap();
+        assert_eq!(
+            parsed,
+            AffineTransformMatrix::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0)
+        );
+        let parsed: AffineTransformMatrix = "matrix(1,2 , 3,4,5, 6)".parse().unwrap();
+        assert_eq!(
+            parsed,
+            AffineTransformMatrix::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0)
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        for invalid in [
+            // wrong arity
+            "matrix(1, 2, 3, 4, 5)",
+            "matrix(1, 2, 3, 4, 5, 6, 7)",
+            "matrix()",
+            // not a number
+            "matrix(1, 2, 3, 4, 5, x)",
+            "matrix(1, 2, 3, 4, 5, 6px)",
+            // not a matrix
+            "translate(1, 2)",
+            "1, 2, 3, 4, 5, 6",
+            "matrix(1, 2, 3, 4, 5, 6",
+            "",
+        ] {
+            let err = invalid.parse::<AffineTransformMatrix>().unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("Expected matrix(a, b, c, d, e, f), got {}", invalid)
+            );
+        }
+    }
+}
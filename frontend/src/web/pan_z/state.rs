@@ -1,17 +1,27 @@
+use crate::geom::{Svg, Transform, ViewPort};
+use crate::svg::{PreserveAspectRatio, SvgPoint};
 use crate::web::pan_z;
-use crate::{AffineTransformMatrix, ViewBox, ViewPortPos, ViewPortRect};
+use crate::{ScreenVec, ViewBox, ViewPortPos, ViewPortRect};
 use approx::abs_diff_eq;
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use wasm_bindgen::JsCast;
-use web_sys::SvgElement;
-
-pub trait PanZoomState<A: Clone + pan_z::PositionedExtent + 'static>: Display {
-    /// Returns the local coordinates of `points`. These coordinates are in the
-    /// internal coordinate system. They may only be compared to another result
-    /// of this function, and only for equality, as distances in the other
-    /// coordinate system may be different from the Html View Port coordinate
-    /// system.
-    fn as_local_coordinates(&self, element: A, point: ViewPortPos) -> (f64, f64);
+use web_sys::{SvgElement, SvgGraphicsElement, SvgsvgElement};
+
+/// The state of a pan-zoom element. States compare equal, iff they show the
+/// same view.
+pub trait PanZoomState<A: Clone + pan_z::PositionedExtent + 'static>: Display + PartialEq {
+    /// A position in the coordinate system of the content, which is not
+    /// affected by panning or zooming.
+    type LocalPoint: Copy + Debug + Display + PartialEq;
+
+    /// Returns the local coordinates of `point`, i.e. the position of the
+    /// content shown at `point`. These coordinates are in the internal
+    /// coordinate system. Distances in it may be different from the Html View
+    /// Port coordinate system.
+    ///
+    /// Returns `None`, if no content is shown at `point`, as the element is
+    /// not rendered or collapsed, e.g. to zero width or by `scale(0)`.
+    fn as_local_coordinates(&self, element: A, point: ViewPortPos) -> Option<Self::LocalPoint>;
     /// Returns width and height in an internal coordinate system. They may only
     /// be compared to other results of this function, as distances in the other
     /// coordinate system may be different from the Html View Port
@@ -19,17 +29,66 @@ pub trait PanZoomState<A: Clone + pan_z::PositionedExtent + 'static>: Display {
     fn unscaled_dimensions(&self, element: A) -> (f64, f64);
 
     fn scale(&self) -> f64;
+    /// In radians, clockwise on screen, as the y-axis points downwards.
+    fn rotation(&self) -> f64;
     fn top_left(&self, element: A) -> ViewPortPos;
     fn bounding_rect(&self, element: A) -> ViewPortRect;
 
     /// Changes the scale to `new_scale`, translating such, that `fix_point`
     /// remains at the same position.
+    ///
+    /// `new_scale` is clamped to the limits of the state. The pan bounds of
+    /// the limits take precedence over the fix point.
     fn set_scale(&mut self, element: A, fix_point: ViewPortPos, new_scale: f64);
+
+    /// Changes the rotation to `new_rotation` radians, translating such, that
+    /// `fix_point` remains at the same position.
+    ///
+    /// The pan bounds of the limits of the state take precedence over the fix
+    /// point.
+    fn set_rotation(&mut self, element: A, fix_point: ViewPortPos, new_rotation: f64);
+
+    /// Whether the state can rotate at all. If not, `set_rotation` has no
+    /// effect.
+    fn can_rotate(&self) -> bool;
+
+    /// Changes scale and rotation to `new_scale` and `new_rotation` and moves
+    /// the content shown at `fix_point` to `fix_point + delta` -- all in a
+    /// single change, as the element's geometry in the DOM is updated only in
+    /// the next animation frame.
+    ///
+    /// `new_scale` is clamped to the limits of the state. The pan bounds of
+    /// the limits take precedence over the fix point. States, that cannot
+    /// rotate, ignore `new_rotation`.
+    fn set_transform(
+        &mut self,
+        element: A,
+        fix_point: ViewPortPos,
+        delta: ScreenVec,
+        new_scale: f64,
+        new_rotation: f64,
+    );
+
+    /// Translates the content by `delta`, i.e. after panning, every point of
+    /// the content appears `delta` view port units from where it appeared
+    /// before -- unless the pan bounds of the limits of the state prevent it.
+    fn pan_by(&mut self, element: A, delta: ScreenVec);
+
+    /// Pans and zooms, such that the whole content is visible and as large as
+    /// possible.
+    fn fit_content(&mut self, element: A);
 }
 
+/// The content is rotated by the `<g>` wrapping it, which
+/// `PanZoomHandle::svg_view_box` creates. Local points are in its user space.
 impl PanZoomState<SvgElement> for ViewBox {
-    fn as_local_coordinates(&self, element: SvgElement, point: ViewPortPos) -> (f64, f64) {
-        todo!()
+    type LocalPoint = SvgPoint;
+
+    fn as_local_coordinates(&self, element: SvgElement, point: ViewPortPos) -> Option<SvgPoint> {
+        let view_port_to_content = svg_to_view_port(&element)?
+            .try_inverse()?
+            .then(self.canvas_to_content());
+        Some(view_port_to_content.apply(point))
     }
 
     fn unscaled_dimensions(&self, _element: SvgElement) -> (f64, f64) {
@@ -41,12 +100,20 @@ impl PanZoomState<SvgElement> for ViewBox {
         self.scale()
     }
 
+    fn rotation(&self) -> f64 {
+        self.rotation()
+    }
+
+    /// The top left corner of the content box, rotated with the content -- or
+    /// the top left corner of the `<svg>` element, if it is not rendered.
     fn top_left(&self, element: SvgElement) -> ViewPortPos {
-        let svg_to_view_port_transformation =
-            AffineTransformMatrix::from(&element.dyn_into().unwrap());
-        let svg_top_left = self.content_box().top_left();
-        let view_port_top_left =
-            ViewPortPos::from_svg_coords(svg_top_left, svg_to_view_port_transformation);
+        let svg_top_left = self
+            .content_to_canvas()
+            .apply(self.content_box().top_left());
+        let view_port_top_left = match svg_to_view_port(&element) {
+            Some(svg_to_view_port) => svg_to_view_port.apply(svg_top_left),
+            None => pan_z::PositionedExtent::top_left(&element),
+        };
         info!(
             "top left of view box is svg {}, view port {}",
             svg_top_left, view_port_top_left
@@ -54,146 +121,366 @@ impl PanZoomState<SvgElement> for ViewBox {
         view_port_top_left
     }
 
-    /// must have preserveAspectRatio "xMidYMid meet"
+    /// The bounds of the rotated content box in the view port, see
+    /// `ViewBox::content_bounds`. They are stretched non-uniformly by
+    /// `PreserveAspectRatio::None`, if the aspect ratios of view box and view
+    /// port differ. If the `<svg>` element is not rendered, it is the element's
+    /// empty bounding rect.
     fn bounding_rect(&self, element: SvgElement) -> ViewPortRect {
-        let svg_to_view_port_transformation =
-            AffineTransformMatrix::from(&element.dyn_into().unwrap());
-
-        let svg_content_top_left = self.content_box().top_left();
-        let view_port_content_top_left =
-            ViewPortPos::from_svg_coords(svg_content_top_left, svg_to_view_port_transformation);
+        let content_box = self.content_bounds();
+        let view_box_view_port = match svg_to_view_port(&element) {
+            Some(svg_to_view_port) => svg_to_view_port.apply_to_rect(content_box),
+            None => return pan_z::PositionedExtent::bounding_rect(&element),
+        };
         info!(
-            "top left of view box is svg {}, view port {}",
-            svg_content_top_left, view_port_content_top_left
+            "content bounds {} are {} in the view port",
+            content_box, view_box_view_port
         );
 
-        let content_bottom_right = ViewPortPos::from_svg_coords(
-            self.content_box().bottom_right(),
-            svg_to_view_port_transformation,
-        );
-        let view_box_view_port = ViewPortRect::new(
-            view_port_content_top_left,
-            content_bottom_right.x() - view_port_content_top_left.x(),
-            content_bottom_right.y() - view_port_content_top_left.y(),
-        );
-
-        let view_box = self.view_box();
+        // The view box may have another aspect ratio than the content, but unless
+        // preserveAspectRatio is "none", it must be scaled uniformly
         debug_assert!(
-            abs_diff_eq!(
-                view_box.aspect_radio(),
-                view_box_view_port.aspect_ratio(),
-                epsilon = 1e-12
-            ),
-            "Aspect ratio of view box in view port coordinate system {} \
+            self.preserve_aspect_ratio() == PreserveAspectRatio::None
+                || view_box_view_port.is_empty()
+                || abs_diff_eq!(
+                    content_box.aspect_ratio(),
+                    view_box_view_port.aspect_ratio(),
+                    epsilon = 1e-12
+                ),
+            "Aspect ratio of content box in view port coordinate system {} \
             does not match that in SVG coordinate system {}",
             view_box_view_port.aspect_ratio(),
-            view_box.aspect_radio()
+            content_box.aspect_ratio()
         );
         view_box_view_port
     }
 
     fn set_scale(&mut self, element: SvgElement, fix_point: ViewPortPos, new_scale: f64) {
+        let rotation = self.rotation();
+        self.set_transform(
+            element,
+            fix_point,
+            ScreenVec::new(0.0, 0.0),
+            new_scale,
+            rotation,
+        );
+    }
+
+    fn set_rotation(&mut self, element: SvgElement, fix_point: ViewPortPos, new_rotation: f64) {
+        let scale = self.scale();
+        self.set_transform(
+            element,
+            fix_point,
+            ScreenVec::new(0.0, 0.0),
+            scale,
+            new_rotation,
+        );
+    }
+
+    fn can_rotate(&self) -> bool {
+        true
+    }
+
+    fn set_transform(
+        &mut self,
+        element: SvgElement,
+        fix_point: ViewPortPos,
+        delta: ScreenVec,
+        new_scale: f64,
+        new_rotation: f64,
+    ) {
+        let view_port_to_svg = match view_port_to_svg(&element) {
+            Some(view_port_to_svg) => view_port_to_svg,
+            None => return,
+        };
+        record_view_port(self, &element);
         let old_scale: f64 = self.scale();
+        let new_scale = self.limits().clamp_scale(new_scale);
         warn!(
-            "Changing scale from {} to {} with fix point {}",
-            old_scale, new_scale, fix_point
+            "Changing scale from {} to {} and rotation from {} to {} with fix point {}, moving it by {}",
+            old_scale,
+            new_scale,
+            self.rotation(),
+            new_rotation,
+            fix_point,
+            delta
         );
-        let svg_to_view_port_transformation =
-            AffineTransformMatrix::from(&element.dyn_into().unwrap());
-        let view_port_to_svg_transformation =
-            svg_to_view_port_transformation.try_inverse().unwrap();
-        let fix_point_svg = fix_point.to_svg_coords(view_port_to_svg_transformation);
-        let old_scale_top_left_svg = self.top_left();
-        let old_scale_fixpoint_offset_from_top_left = fix_point_svg - old_scale_top_left_svg;
-        let new_scale_fixpoint_offset_from_new_scale_top_left =
-            old_scale_fixpoint_offset_from_top_left * (old_scale / new_scale);
-        let new_scale_top_left_svg =
-            fix_point_svg - new_scale_fixpoint_offset_from_new_scale_top_left;
+        // rotating pans the view box, such that the fix point keeps showing the
+        // same content, and leaves the view box axis-aligned
+        let before_rotation = self.top_left();
+        self.set_rotation_around(new_rotation, view_port_to_svg.apply(fix_point));
+        let fix_point_svg = view_port_to_svg.apply(fix_point) + (self.top_left() - before_rotation);
+        // the DOM still shows the old scale, the view box moves opposite to the content
+        let new_scale_delta_svg = view_port_to_svg.apply_to_vector(delta) * (old_scale / new_scale);
+        let new_scale_top_left_svg = self
+            .view_box()
+            .scale_about(fix_point_svg, old_scale / new_scale)
+            .top_left()
+            - new_scale_delta_svg;
 
-        self.set_top_left(new_scale_top_left_svg);
+        // the pan bounds depend on the size of the view box, i.e. on the new scale
         self.set_scale(new_scale);
-        debug_assert_eq!(self.top_left(), new_scale_top_left_svg);
+        self.set_top_left(new_scale_top_left_svg);
+        // clamping the visible part instead of the view box may round differently
+        debug_assert!(
+            (self.top_left() - self.clamp_top_left(new_scale_top_left_svg)).length()
+                <= 1e-9 * self.width().max(self.height()),
+            "{} is not clamped to the pan bounds",
+            self
+        );
+    }
+
+    fn pan_by(&mut self, element: SvgElement, delta: ScreenVec) {
+        let view_port_to_svg = match view_port_to_svg(&element) {
+            Some(view_port_to_svg) => view_port_to_svg,
+            None => return,
+        };
+        record_view_port(self, &element);
+        // moving the content by `delta` means moving the view box by `-delta`
+        let delta_svg = view_port_to_svg.apply_to_vector(delta);
+        self.set_top_left(self.top_left() - delta_svg);
+    }
+
+    fn fit_content(&mut self, element: SvgElement) {
+        record_view_port(self, &element);
+        self.fit_content(0.0);
+    }
+}
+
+/// Lets `view_box` know the shape of the view port, the `<svg>` element, so
+/// that its pan bounds refer to the visible part of the canvas -- even if it
+/// is not kept in sync by `sync_view_box_with_view_port`.
+fn record_view_port(view_box: &mut ViewBox, element: &SvgElement) {
+    let view_port = pan_z::PositionedExtent::bounding_rect(element);
+    if !view_port.is_empty() {
+        view_box.set_view_port_aspect_ratio(view_port.aspect_ratio());
+    }
+}
+
+/// The screen CTM of the `<svg>` element, i.e. the transformation by its
+/// `viewBox` -- or `None`, if the element is not rendered.
+fn svg_to_view_port(element: &SvgElement) -> Option<Transform<Svg, ViewPort>> {
+    let svg: &SvgGraphicsElement = element.dyn_ref::<SvgsvgElement>()?;
+    pan_z::screen_ctm(svg)
+}
+
+/// The inverse of `svg_to_view_port` -- or `None` with a warning, if the
+/// `<svg>` element is not rendered or collapsed to zero width or height, so
+/// that no change of the view can be shown.
+fn view_port_to_svg(element: &SvgElement) -> Option<Transform<ViewPort, Svg>> {
+    let inverse = svg_to_view_port(element).and_then(|transform| transform.try_inverse());
+    if inverse.is_none() {
+        warn!("The svg element is not rendered or has no extent, ignoring the change");
     }
+    inverse
 }
 
 pub mod view_state {
     use approx::relative_eq;
     use std::fmt::{Display, Formatter};
 
-    use num_traits::Zero;
-    use zoon::Mutable;
+    use web_sys::HtmlElement;
     use zoon::*;
 
-    use crate::web::pan_z::screen_geom;
+    use crate::geom::{ElementLocal, Transform, ViewPort};
+    #[cfg(feature = "serde")]
+    use crate::serialization::{validate_rotation, validate_scale, InvalidViewErr, ViewStateRepr};
+    use crate::web::pan_z::animation::{interpolate_angle, Interpolate};
+    use crate::web::pan_z::deep_link::{parse_numbers, UrlEncode};
+    use crate::web::pan_z::screen_geom::{self, PositionedExtent};
     use crate::web::pan_z::state::PanZoomState;
-    use crate::{ScreenVec, ViewPortPos, ViewPortRect};
+    use crate::{
+        AffineTransformMatrix, ElementPos, ElementRect, ElementVec, PanBounds, PanZoomLimits,
+        ScreenVec, ViewPortPos, ViewPortRect,
+    };
 
-    #[derive(Debug, Copy, Clone)]
+    /// The CSS transform `translate(top_left) rotate(rotation) scale(scale)`
+    /// with transform origin `0 0`.
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "serde",
+        derive(serde::Serialize, serde::Deserialize),
+        serde(into = "ViewStateRepr", try_from = "ViewStateRepr")
+    )]
     pub struct ViewState {
         top_left: ViewPortPos,
         scale: f64,
+        /// In radians
+        rotation: f64,
+        /// The pan bounds refer to the view port, i.e. the browser window, as
+        /// the visible area.
+        limits: PanZoomLimits,
     }
 
-    impl<A: Clone + screen_geom::PositionedExtent + 'static> PanZoomState<A> for ViewState {
-        fn as_local_coordinates(&self, element: A, point: ViewPortPos) -> (f64, f64) {
-            todo!()
+    impl<A: Clone + screen_geom::PositionedExtent + AsRef<HtmlElement> + 'static> PanZoomState<A>
+        for ViewState
+    {
+        type LocalPoint = ElementPos;
+
+        /// Inverts `translate(top_left) rotate(rotation) scale(scale)` with
+        /// transform origin `0 0`.
+        fn as_local_coordinates(&self, element: A, point: ViewPortPos) -> Option<ElementPos> {
+            self.to_local(&element, point)
         }
         fn unscaled_dimensions(&self, element: A) -> (f64, f64) {
-            let scaled_width = element.bounding_rect().width();
-            let scaled_height = element.bounding_rect().height();
-            (scaled_width / self.scale, scaled_height / self.scale)
+            let layout_size = self.layout_size(&element);
+            (layout_size.x(), layout_size.y())
         }
 
         fn scale(&self) -> f64 {
             self.scale
         }
 
+        fn rotation(&self) -> f64 {
+            self.rotation
+        }
+
         fn top_left(&self, _element: A) -> ViewPortPos {
             self.top_left
         }
 
+        /// The browser's bounding rect, which encloses the rotated element.
         fn bounding_rect(&self, element: A) -> ViewPortRect {
             element.bounding_rect()
         }
 
         fn set_scale(&mut self, element: A, fix_point: ViewPortPos, new_scale: f64) {
-            let old_scale: f64 = <Self as PanZoomState<A>>::scale(self);
-            // distance from top left of zoom element in view port units
-            let fixpoint_offset_from_top_left: ScreenVec =
-                self.bounding_rect(element.clone()).offset(fix_point);
+            let new_scale = self.limits.clamp_scale(new_scale);
             warn!(
-                "Computing translation for scale change from {} to {}, \n\
-                with top-left relative fix point {}",
-                old_scale, new_scale, fixpoint_offset_from_top_left
+                "Changing scale from {} to {} with fix point {}",
+                self.scale, new_scale, fix_point
             );
+            self.transform_around(&element, fix_point, fix_point, self.rotation, new_scale);
+        }
 
-            let scale_ratio = new_scale / old_scale;
-            info!(
-                "scale ratio {} = new scale {} / old scale {}",
-                scale_ratio, new_scale, old_scale
+        fn set_rotation(&mut self, element: A, fix_point: ViewPortPos, new_rotation: f64) {
+            warn!(
+                "Changing rotation from {} to {} with fix point {}",
+                self.rotation, new_rotation, fix_point
             );
+            self.transform_around(&element, fix_point, fix_point, new_rotation, self.scale);
+        }
 
-            let fix_point_stabilizing_translation = if relative_eq!(
+        fn can_rotate(&self) -> bool {
+            true
+        }
+
+        fn set_transform(
+            &mut self,
+            element: A,
+            fix_point: ViewPortPos,
+            delta: ScreenVec,
+            new_scale: f64,
+            new_rotation: f64,
+        ) {
+            let new_scale = self.limits.clamp_scale(new_scale);
+            info!(
+                "Transforming {} to scale {} and rotation {} with fix point {}, moving it by {}",
+                self, new_scale, new_rotation, fix_point, delta
+            );
+            self.transform_around(
+                &element,
+                fix_point,
+                fix_point + delta,
+                new_rotation,
                 new_scale,
-                old_scale,
-                epsilon = f32::EPSILON as f64,
-                max_relative = f32::EPSILON as f64
-            ) {
-                ScreenVec::zero()
-            } else {
-                let fix_point_stabilizing_translation =
-                    fixpoint_offset_from_top_left * (1.0 - scale_ratio);
-                info!(
-                    "pointer_focus_stable_scaled_top_left: {} \
-                    = fixpoint_offset_from_top_left {} * (1.0 - actual_scale_change {})",
-                    fix_point_stabilizing_translation, fixpoint_offset_from_top_left, scale_ratio
-                );
-                fix_point_stabilizing_translation
-            };
-            let new_top_left = self.top_left(element) + fix_point_stabilizing_translation;
+            );
+        }
 
-            self.scale = new_scale;
-            self.top_left = new_top_left;
+        fn pan_by(&mut self, element: A, delta: ScreenVec) {
+            info!("Panning {} by {}", self, delta);
+            self.top_left =
+                self.clamp_top_left(&element, self.top_left + delta, self.rotation, self.scale);
+        }
+
+        /// Fits the element into the view port, i.e. the browser window, and
+        /// centers it.
+        fn fit_content(&mut self, element: A) {
+            self.fit_content(&element, 0.0);
+        }
+    }
+
+    /// Interpolates the scale geometrically -- so that zooming appears to have
+    /// a constant speed -- the translation linearly and the rotation the
+    /// shorter way round. The limits are the target's.
+    impl Interpolate for ViewState {
+        fn interpolate(&self, target: &Self, t: f64) -> Self {
+            Self {
+                top_left: self.top_left + (target.top_left - self.top_left) * t,
+                scale: self.scale.powf(1.0 - t) * target.scale.powf(t),
+                rotation: interpolate_angle(self.rotation, target.rotation, t),
+                limits: target.limits,
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl From<ViewState> for ViewStateRepr {
+        fn from(view_state: ViewState) -> Self {
+            Self::V1 {
+                top_left: view_state.top_left.into(),
+                scale: view_state.scale,
+                rotation: view_state.rotation,
+                limits: view_state.limits.into(),
+            }
+        }
+    }
+
+    /// Rejects values, that are not finite, a scale, that is not positive,
+    /// and invalid limits. The scale is clamped to the limits.
+    #[cfg(feature = "serde")]
+    impl TryFrom<ViewStateRepr> for ViewState {
+        type Error = InvalidViewErr;
+
+        fn try_from(repr: ViewStateRepr) -> Result<Self, Self::Error> {
+            match repr {
+                ViewStateRepr::V1 {
+                    top_left,
+                    scale,
+                    rotation,
+                    limits,
+                } => {
+                    let limits: PanZoomLimits = limits.try_into()?;
+                    Ok(Self {
+                        top_left: top_left.try_into()?,
+                        scale: limits.clamp_scale(validate_scale(scale)?),
+                        rotation: validate_rotation(rotation)?,
+                        limits,
+                    })
+                }
+            }
+        }
+    }
+
+    /// `x,y,scale,rotation`: the local point of the element shown in the
+    /// center of the view port, scale and rotation in radians -- so that the
+    /// link shows the same content in windows of any size.
+    impl<A: PositionedExtent + AsRef<HtmlElement>> UrlEncode<A> for ViewState {
+        fn to_url_value(&self, element: &A) -> Option<String> {
+            let center = self.to_local(element, window().bounding_rect().center())?;
+            Some(format!(
+                "{},{},{},{}",
+                center.x(),
+                center.y(),
+                self.scale,
+                self.rotation
+            ))
+        }
+
+        fn from_url_value(current: &Self, element: &A, value: &str) -> Option<Self> {
+            let [x, y, scale, rotation] = parse_numbers(value)?;
+            if scale <= 0.0 {
+                return None;
+            }
+            let mut view_state = *current;
+            view_state.transform_around(
+                element,
+                current.to_view_port(element, ElementPos::new(x, y)),
+                window().bounding_rect().center(),
+                rotation,
+                current.limits.clamp_scale(scale),
+            );
+            Some(view_state)
         }
     }
 
@@ -201,23 +488,704 @@ pub mod view_state {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
             write!(
                 f,
-                "ViewState{{top left: {}, scale: {}}}",
-                self.top_left, self.scale
+                "ViewState{{top left: {}, scale: {}, rotation: {}}}",
+                self.top_left, self.scale, self.rotation
             )
         }
     }
 
+    impl Default for ViewState {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
     impl ViewState {
-        fn new() -> Self {
+        pub fn new() -> Self {
             Self {
                 top_left: Default::default(),
                 scale: 1.0,
+                rotation: 0.0,
+                limits: PanZoomLimits::default(),
+            }
+        }
+
+        pub fn limits(&self) -> PanZoomLimits {
+            self.limits
+        }
+
+        /// Changes the limits and brings scale and position within them.
+        pub fn set_limits<A: PositionedExtent + AsRef<HtmlElement>>(
+            &mut self,
+            element: &A,
+            limits: PanZoomLimits,
+        ) {
+            let scale = limits.clamp_scale(self.scale);
+            self.limits = limits;
+            // the origin is computed from the DOM, which still shows the old scale
+            self.top_left = self.clamp_top_left(element, self.top_left, self.rotation, scale);
+            self.scale = scale;
+        }
+
+        /// Changes the limits of a state, that is not shown yet -- e.g. a
+        /// restored one -- and brings the scale within them. The pan bounds
+        /// are enforced with the next change of position, as they depend on
+        /// the element.
+        #[cfg(feature = "persistence")]
+        pub(crate) fn set_limits_unshown(&mut self, limits: PanZoomLimits) {
+            self.limits = limits;
+            self.scale = limits.clamp_scale(self.scale);
+        }
+
+        /// Pans and zooms, such that the part of the element currently shown
+        /// in `rect` fills the view port, i.e. the browser window, and is
+        /// centered in it. If the aspect ratios differ, `rect` is extended in
+        /// one dimension, like with `preserveAspectRatio="xMidYMid meet"`.
+        pub fn zoom_to_rect<A: PositionedExtent + AsRef<HtmlElement>>(
+            &mut self,
+            element: &A,
+            rect: ViewPortRect,
+        ) {
+            self.zoom_rect_into(element, rect, window().bounding_rect());
+        }
+
+        /// Fits the element into the view port, i.e. the browser window, and
+        /// centers it, leaving at least `padding` view port units to each
+        /// side.
+        pub fn fit_content<A: PositionedExtent + AsRef<HtmlElement>>(
+            &mut self,
+            element: &A,
+            padding: f64,
+        ) {
+            let view_port = window().bounding_rect();
+            let target = view_port.deflate(ScreenVec::new(padding, padding));
+            self.zoom_rect_into(element, element.bounding_rect(), target);
+        }
+
+        /// Pans and zooms, such that the part of the element currently shown
+        /// in `rect` is shown in `target`.
+        fn zoom_rect_into<A: PositionedExtent + AsRef<HtmlElement>>(
+            &mut self,
+            element: &A,
+            rect: ViewPortRect,
+            target: ViewPortRect,
+        ) {
+            let new_scale = self.limits.clamp_scale(
+                self.scale
+                    * f64::min(
+                        target.width() / rect.width(),
+                        target.height() / rect.height(),
+                    ),
+            );
+            let scale_ratio = new_scale / self.scale;
+            info!(
+                "Zooming {} from {} into {} with scale {}",
+                self, rect, target, new_scale
+            );
+            let origin = self.origin(element);
+            let new_origin = target.center() + (origin - rect.center()) * scale_ratio;
+            // top_left is a translation of the element's layout position
+            let new_top_left = self.top_left + (new_origin - origin);
+            self.top_left = self.clamp_top_left(element, new_top_left, self.rotation, new_scale);
+            self.scale = new_scale;
+        }
+
+        /// Changes rotation and scale, translating such, that the content
+        /// shown at `fix_point` moves to `target` -- unless the pan bounds
+        /// prevent it.
+        fn transform_around<A: PositionedExtent + AsRef<HtmlElement>>(
+            &mut self,
+            element: &A,
+            fix_point: ViewPortPos,
+            target: ViewPortPos,
+            rotation: f64,
+            scale: f64,
+        ) {
+            let unchanged = rotation == self.rotation
+                && relative_eq!(
+                    scale,
+                    self.scale,
+                    epsilon = f32::EPSILON as f64,
+                    max_relative = f32::EPSILON as f64
+                );
+            let new_top_left = if unchanged {
+                self.top_left + (target - fix_point)
+            } else {
+                // the offset of the fix point from the origin, i.e. the transformed
+                // top-left corner of the element, in local units
+                let origin = self.origin(element);
+                let fix_point_offset = fix_point - origin;
+                let local_offset = self
+                    .linear_transform()
+                    .try_inverse()
+                    .unwrap()
+                    .apply_to_vector(fix_point_offset);
+                let new_origin =
+                    target - linear_transform(rotation, scale).apply_to_vector(local_offset);
+                info!(
+                    "Moving origin from {} to {} to move fix point {} to {}",
+                    origin, new_origin, fix_point, target
+                );
+                // top_left is a translation of the element's layout position
+                self.top_left + (new_origin - origin)
+            };
+            self.top_left = self.clamp_top_left(element, new_top_left, rotation, scale);
+            self.rotation = rotation;
+            self.scale = scale;
+        }
+
+        /// Inverts `translate(top_left) rotate(rotation) scale(scale)` with
+        /// transform origin `0 0`.
+        fn to_local<A: PositionedExtent + AsRef<HtmlElement>>(
+            &self,
+            element: &A,
+            point: ViewPortPos,
+        ) -> Option<ElementPos> {
+            let offset = point - self.origin(element);
+            Some(
+                ElementPos::origin()
+                    + self
+                        .linear_transform()
+                        .try_inverse()?
+                        .apply_to_vector(offset),
+            )
+        }
+
+        /// Returns where the local point of the element is shown.
+        fn to_view_port<A: PositionedExtent + AsRef<HtmlElement>>(
+            &self,
+            element: &A,
+            point: ElementPos,
+        ) -> ViewPortPos {
+            self.origin(element) + self.linear_transform().apply_to_vector(point.as_vec())
+        }
+
+        /// `rotate(rotation) scale(scale)`, i.e. the transform without the
+        /// translation.
+        fn linear_transform(&self) -> Transform<ElementLocal, ViewPort> {
+            linear_transform(self.rotation, self.scale)
+        }
+
+        /// Returns the position of the element's local origin, i.e. its
+        /// transformed top-left corner.
+        fn origin<A: PositionedExtent + AsRef<HtmlElement>>(&self, element: &A) -> ViewPortPos {
+            let (min_offset, _) =
+                transformed_extent(self.layout_size(element), self.rotation, self.scale);
+            element.bounding_rect().top_left() - min_offset
+        }
+
+        /// Returns the size of the element without transform.
+        ///
+        /// Without rotation, it is derived from the bounding rect. Otherwise,
+        /// the browser only reports it rounded to integer CSS pixels.
+        fn layout_size<A: PositionedExtent + AsRef<HtmlElement>>(&self, element: &A) -> ElementVec {
+            if self.rotation == 0.0 {
+                let bounding_rect = element.bounding_rect();
+                ElementVec::new(
+                    bounding_rect.width() / self.scale,
+                    bounding_rect.height() / self.scale,
+                )
+            } else {
+                let element: &HtmlElement = element.as_ref();
+                ElementVec::new(
+                    element.offset_width() as f64,
+                    element.offset_height() as f64,
+                )
+            }
+        }
+
+        /// Returns the translation closest to `top_left`, that keeps the
+        /// element with `rotation` and `scale` within the pan bounds of the
+        /// view port.
+        fn clamp_top_left<A: PositionedExtent + AsRef<HtmlElement>>(
+            &self,
+            element: &A,
+            top_left: ViewPortPos,
+            rotation: f64,
+            scale: f64,
+        ) -> ViewPortPos {
+            if self.limits.pan_bounds == PanBounds::Unbounded {
+                return top_left;
             }
+            let view_port = window().bounding_rect();
+            // top_left is a translation of the element's layout position
+            let layout_top_left = self.origin(element) - self.top_left;
+            let (min_offset, max_offset) =
+                transformed_extent(self.layout_size(element), rotation, scale);
+            let extent = max_offset - min_offset;
+            let clamped_element_left = self.limits.clamp_pan(
+                layout_top_left.x() + top_left.x() + min_offset.x(),
+                extent.x(),
+                view_port.left(),
+                view_port.width(),
+            );
+            let clamped_element_top = self.limits.clamp_pan(
+                layout_top_left.y() + top_left.y() + min_offset.y(),
+                extent.y(),
+                view_port.top(),
+                view_port.height(),
+            );
+            ViewPortPos::new(
+                clamped_element_left - layout_top_left.x() - min_offset.x(),
+                clamped_element_top - layout_top_left.y() - min_offset.y(),
+            )
+        }
+    }
+
+    fn linear_transform(rotation: f64, scale: f64) -> Transform<ElementLocal, ViewPort> {
+        Transform::new(
+            AffineTransformMatrix::rotation(rotation)
+                * AffineTransformMatrix::scaling(scale, scale),
+        )
+    }
+
+    /// Returns the offsets of the top-left and the bottom-right corner of the
+    /// bounding rect of a box of `size`, that is rotated and scaled around its
+    /// top-left corner, from that corner.
+    fn transformed_extent(size: ElementVec, rotation: f64, scale: f64) -> (ScreenVec, ScreenVec) {
+        let extent = linear_transform(rotation, scale)
+            .apply_to_rect(ElementRect::new(ElementPos::origin(), size));
+        (extent.top_left().as_vec(), extent.bottom_right().as_vec())
+    }
+}
+
+pub mod element_transform {
+    use std::fmt::{Display, Formatter};
+
+    use wasm_bindgen::JsCast;
+    use web_sys::{SvgElement, SvgGraphicsElement};
+
+    use crate::geom::{Point, Rect, Svg, SvgLocal, Transform, Vector, ViewPort};
+    #[cfg(feature = "serde")]
+    use crate::serialization::{
+        validate_rotation, validate_scale, ElementTransformRepr, InvalidViewErr,
+    };
+    use crate::svg::{SvgVec, ToSvgString};
+    use crate::web::pan_z::deep_link::{parse_numbers, UrlEncode};
+    use crate::web::pan_z::screen_geom::{screen_ctm, PositionedExtent};
+    use crate::web::pan_z::state::PanZoomState;
+    use crate::{AffineTransformMatrix, PanZoomLimits, ScreenVec, ViewPortPos, ViewPortRect};
+
+    /// Pans, zooms and rotates a single `SvgGraphicsElement` -- e.g. a `<g>` --
+    /// inside a larger SVG by its `transform` attribute, which is
+    /// `translate(translate) rotate(rotation) scale(scale)`.
+    ///
+    /// The translation is in the user space of the element's parent, i.e. the
+    /// coordinate system the `transform` attribute applies in. Coordinates are
+    /// mapped to and from the view port by the parent's screen CTM, so
+    /// transforms of ancestors -- including the root `viewBox` -- are taken
+    /// into account.
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "serde",
+        derive(serde::Serialize, serde::Deserialize),
+        serde(into = "ElementTransformRepr", try_from = "ElementTransformRepr")
+    )]
+    pub struct ElementTransform {
+        translate: SvgVec,
+        scale: f64,
+        /// In radians
+        rotation: f64,
+        /// The pan bounds refer to the owner SVG element as the visible area.
+        limits: PanZoomLimits,
+    }
+
+    impl Default for ElementTransform {
+        fn default() -> Self {
+            Self {
+                translate: SvgVec::new(0.0, 0.0),
+                scale: 1.0,
+                rotation: 0.0,
+                limits: PanZoomLimits::default(),
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl From<ElementTransform> for ElementTransformRepr {
+        fn from(element_transform: ElementTransform) -> Self {
+            Self::V1 {
+                translate: element_transform.translate.into(),
+                scale: element_transform.scale,
+                rotation: element_transform.rotation,
+                limits: element_transform.limits.into(),
+            }
+        }
+    }
+
+    /// Rejects values, that are not finite, a scale, that is not positive,
+    /// and invalid limits. The scale is clamped to the limits.
+    #[cfg(feature = "serde")]
+    impl TryFrom<ElementTransformRepr> for ElementTransform {
+        type Error = InvalidViewErr;
+
+        fn try_from(repr: ElementTransformRepr) -> Result<Self, Self::Error> {
+            match repr {
+                ElementTransformRepr::V1 {
+                    translate,
+                    scale,
+                    rotation,
+                    limits,
+                } => {
+                    let limits: PanZoomLimits = limits.try_into()?;
+                    Ok(Self {
+                        translate: translate.try_into()?,
+                        scale: limits.clamp_scale(validate_scale(scale)?),
+                        rotation: validate_rotation(rotation)?,
+                        limits,
+                    })
+                }
+            }
+        }
+    }
+
+    /// `x,y,scale,rotation`: the point in the element's user space shown in
+    /// the center of its owner SVG element, scale and rotation in radians --
+    /// so that the link shows the same content in SVGs of any size.
+    impl UrlEncode<SvgElement> for ElementTransform {
+        fn to_url_value(&self, element: &SvgElement) -> Option<String> {
+            let placement = Placement::of(element)?;
+            let center = self
+                .local_to_view_port(&placement)
+                .try_inverse()?
+                .apply(placement.visible.center());
+            Some(format!(
+                "{},{},{},{}",
+                center.x(),
+                center.y(),
+                self.scale,
+                self.rotation
+            ))
+        }
+
+        fn from_url_value(current: &Self, element: &SvgElement, value: &str) -> Option<Self> {
+            let [x, y, scale, rotation] = parse_numbers(value)?;
+            if scale <= 0.0 {
+                return None;
+            }
+            let placement = Placement::of(element)?;
+            let mut element_transform = *current;
+            element_transform.transform_around(
+                &placement,
+                current
+                    .local_to_view_port(&placement)
+                    .apply(Point::new(x, y)),
+                placement.visible.center(),
+                rotation,
+                current.limits.clamp_scale(scale),
+            );
+            Some(element_transform)
+        }
+    }
+
+    impl Display for ElementTransform {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "ElementTransform {{{}}}", self.to_svg_string())
+        }
+    }
+
+    /// SVG's `rotate` takes degrees.
+    impl ToSvgString for ElementTransform {
+        fn to_svg_string(&self) -> String {
+            format!(
+                "translate({} {}) rotate({}) scale({})",
+                self.translate.x(),
+                self.translate.y(),
+                self.rotation.to_degrees(),
+                self.scale
+            )
+        }
+    }
+
+    impl PanZoomState<SvgElement> for ElementTransform {
+        type LocalPoint = Point<SvgLocal>;
+
+        fn as_local_coordinates(
+            &self,
+            element: SvgElement,
+            point: ViewPortPos,
+        ) -> Option<Point<SvgLocal>> {
+            let placement = Placement::try_of(&element)?;
+            Some(
+                self.local_to_view_port(&placement)
+                    .try_inverse()?
+                    .apply(point),
+            )
+        }
+
+        /// Zero, if the element is no rendered graphics element.
+        fn unscaled_dimensions(&self, element: SvgElement) -> (f64, f64) {
+            b_box(&element).map_or((0.0, 0.0), |b_box| (b_box.width(), b_box.height()))
+        }
+
+        fn scale(&self) -> f64 {
+            self.scale
+        }
+
+        fn rotation(&self) -> f64 {
+            self.rotation
+        }
+
+        fn top_left(&self, element: SvgElement) -> ViewPortPos {
+            element.top_left()
+        }
+
+        /// The browser's bounding rect, which encloses the rotated element.
+        fn bounding_rect(&self, element: SvgElement) -> ViewPortRect {
+            element.bounding_rect()
+        }
+
+        fn set_scale(&mut self, element: SvgElement, fix_point: ViewPortPos, new_scale: f64) {
+            let new_scale = self.limits.clamp_scale(new_scale);
+            warn!(
+                "Changing scale from {} to {} with fix point {}",
+                self.scale, new_scale, fix_point
+            );
+            if let Some(placement) = Placement::of(&element) {
+                self.transform_around(&placement, fix_point, fix_point, self.rotation, new_scale);
+            }
+        }
+
+        fn set_rotation(&mut self, element: SvgElement, fix_point: ViewPortPos, new_rotation: f64) {
+            warn!(
+                "Changing rotation from {} to {} with fix point {}",
+                self.rotation, new_rotation, fix_point
+            );
+            if let Some(placement) = Placement::of(&element) {
+                self.transform_around(&placement, fix_point, fix_point, new_rotation, self.scale);
+            }
+        }
+
+        fn can_rotate(&self) -> bool {
+            true
+        }
+
+        fn set_transform(
+            &mut self,
+            element: SvgElement,
+            fix_point: ViewPortPos,
+            delta: ScreenVec,
+            new_scale: f64,
+            new_rotation: f64,
+        ) {
+            let new_scale = self.limits.clamp_scale(new_scale);
+            info!(
+                "Transforming {} to scale {} and rotation {} with fix point {}, moving it by {}",
+                self, new_scale, new_rotation, fix_point, delta
+            );
+            if let Some(placement) = Placement::of(&element) {
+                self.transform_around(
+                    &placement,
+                    fix_point,
+                    fix_point + delta,
+                    new_rotation,
+                    new_scale,
+                );
+            }
+        }
+
+        fn pan_by(&mut self, element: SvgElement, delta: ScreenVec) {
+            let placement = match Placement::of(&element) {
+                Some(placement) => placement,
+                None => return,
+            };
+            let new_translate =
+                self.translate + placement.view_port_to_parent.apply_to_vector(delta);
+            self.translate =
+                self.clamp_translate(&placement, new_translate, self.rotation, self.scale);
+        }
+
+        /// Fits the element into its owner SVG element and centers it.
+        fn fit_content(&mut self, element: SvgElement) {
+            let target = match Placement::of(&element) {
+                Some(placement) => placement.visible,
+                None => return,
+            };
+            let rect = element.bounding_rect();
+            if rect.is_empty() {
+                warn!("{} has no extent, it cannot be fitted", self);
+                return;
+            }
+            let new_scale = self.limits.clamp_scale(
+                self.scale
+                    * f64::min(
+                        target.width() / rect.width(),
+                        target.height() / rect.height(),
+                    ),
+            );
+            // first zoom around the center of the element, then move that center
+            self.set_scale(element.clone(), rect.center(), new_scale);
+            self.pan_by(element, target.center() - rect.center());
+        }
+    }
+
+    impl ElementTransform {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn translate(&self) -> SvgVec {
+            self.translate
+        }
+
+        pub fn limits(&self) -> PanZoomLimits {
+            self.limits
+        }
+
+        /// Changes the limits and brings scale and translation within them.
+        /// The pan bounds are enforced with the next change of position, if
+        /// the element is not rendered.
+        pub fn set_limits(&mut self, element: &SvgElement, limits: PanZoomLimits) {
+            self.limits = limits;
+            self.scale = limits.clamp_scale(self.scale);
+            if let Some(placement) = Placement::of(element) {
+                self.translate =
+                    self.clamp_translate(&placement, self.translate, self.rotation, self.scale);
+            }
+        }
+
+        /// Changes the limits of a state, that is not shown yet -- e.g. a
+        /// restored one -- and brings the scale within them. The pan bounds
+        /// are enforced with the next change of position, as they depend on
+        /// the element.
+        #[cfg(feature = "persistence")]
+        pub(crate) fn set_limits_unshown(&mut self, limits: PanZoomLimits) {
+            self.limits = limits;
+            self.scale = limits.clamp_scale(self.scale);
+        }
+
+        /// The `transform` attribute as a matrix, mapping the element's local
+        /// user space to its parent's.
+        fn local_to_parent(&self) -> Transform<SvgLocal, Svg> {
+            local_to_parent(self.translate, self.rotation, self.scale)
+        }
+
+        /// Maps the element's local user space to the view port.
+        fn local_to_view_port(&self, placement: &Placement) -> Transform<SvgLocal, ViewPort> {
+            self.local_to_parent().then(placement.parent_to_view_port)
+        }
+
+        /// Changes rotation and scale, translating such, that the content
+        /// shown at `fix_point` moves to `target` -- unless the pan bounds
+        /// prevent it.
+        fn transform_around(
+            &mut self,
+            placement: &Placement,
+            fix_point: ViewPortPos,
+            target: ViewPortPos,
+            rotation: f64,
+            scale: f64,
+        ) {
+            // The fix point is shown at `rotate(rotation) scale(scale) local + translate`
+            // in the parent's user space and must be shown at the target.
+            let parent_fix_point = placement.view_port_to_parent.apply(fix_point);
+            let parent_target = placement.view_port_to_parent.apply(target);
+            // the scale is positive, so the transform attribute is invertible
+            let local_fix_point = self
+                .local_to_parent()
+                .try_inverse()
+                .unwrap()
+                .apply(parent_fix_point);
+            let new_translate = parent_target.as_vec()
+                - local_to_parent(SvgVec::new(0.0, 0.0), rotation, scale)
+                    .apply_to_vector(local_fix_point.as_vec());
+            self.translate = self.clamp_translate(placement, new_translate, rotation, scale);
+            self.rotation = rotation;
+            self.scale = scale;
+        }
+
+        /// Returns the translation closest to `translate`, that keeps the
+        /// element with `rotation` and `scale` within the pan bounds of its
+        /// owner SVG element.
+        fn clamp_translate(
+            &self,
+            placement: &Placement,
+            translate: SvgVec,
+            rotation: f64,
+            scale: f64,
+        ) -> SvgVec {
+            if self.limits.pan_bounds == crate::PanBounds::Unbounded {
+                return translate;
+            }
+            let visible = placement.visible;
+            let local_to_view_port =
+                local_to_parent(translate, rotation, scale).then(placement.parent_to_view_port);
+            let bounds = local_to_view_port.apply_to_rect(placement.b_box);
+            let (left, top) = (bounds.left(), bounds.top());
+
+            let clamped_left =
+                self.limits
+                    .clamp_pan(left, bounds.width(), visible.left(), visible.width());
+            let clamped_top =
+                self.limits
+                    .clamp_pan(top, bounds.height(), visible.top(), visible.height());
+            let view_port_correction = ScreenVec::new(clamped_left - left, clamped_top - top);
+            translate
+                + placement
+                    .view_port_to_parent
+                    .apply_to_vector(view_port_correction)
+        }
+    }
+
+    fn local_to_parent(translate: SvgVec, rotation: f64, scale: f64) -> Transform<SvgLocal, Svg> {
+        Transform::new(
+            AffineTransformMatrix::translation(translate.x(), translate.y())
+                * AffineTransformMatrix::rotation(rotation)
+                * AffineTransformMatrix::scaling(scale, scale),
+        )
+    }
+
+    /// The geometry of the element in the DOM, which an `ElementTransform`
+    /// depends on.
+    struct Placement {
+        /// Maps the user space of the element's parent -- where its
+        /// `transform` attribute applies -- to the view port.
+        ///
+        /// Unlike the element's own screen CTM, it does not depend on the
+        /// `transform` attribute, which the DOM updates only in the next
+        /// animation frame.
+        parent_to_view_port: Transform<Svg, ViewPort>,
+        view_port_to_parent: Transform<ViewPort, Svg>,
+        /// The bounding box of the element in its local user space
+        b_box: Rect<SvgLocal>,
+        /// The owner SVG element, i.e. the visible area
+        visible: ViewPortRect,
+    }
+
+    impl Placement {
+        /// Returns `None` with a warning, if the element is no graphics
+        /// element, is not inside a rendered SVG or its parent is collapsed,
+        /// e.g. by `scale(0)`. Changes of the state are skipped then.
+        fn of(element: &SvgElement) -> Option<Self> {
+            let placement = Self::try_of(element);
+            if placement.is_none() {
+                warn!("The element is no rendered SVG graphics element, ignoring the change");
+            }
+            placement
+        }
+
+        fn try_of(element: &SvgElement) -> Option<Self> {
+            let parent = element
+                .parent_element()?
+                .dyn_into::<SvgGraphicsElement>()
+                .ok()?;
+            let parent_to_view_port = screen_ctm(&parent)?;
+            Some(Self {
+                parent_to_view_port,
+                view_port_to_parent: parent_to_view_port.try_inverse()?,
+                b_box: b_box(element)?,
+                visible: element.owner_svg_element()?.bounding_rect(),
+            })
         }
     }
 
-    #[static_ref]
-    pub fn view_state() -> &'static Mutable<ViewState> {
-        Mutable::new(ViewState::new())
+    /// The bounding box of the element in its local user space -- or `None`,
+    /// if it is no graphics element or not rendered.
+    fn b_box(element: &SvgElement) -> Option<Rect<SvgLocal>> {
+        let b_box = element.dyn_ref::<SvgGraphicsElement>()?.get_b_box().ok()?;
+        Some(Rect::new(
+            Point::new(b_box.x() as f64, b_box.y() as f64),
+            Vector::new(b_box.width() as f64, b_box.height() as f64),
+        ))
     }
 }
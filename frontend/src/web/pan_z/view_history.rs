@@ -0,0 +1,438 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use approx::relative_eq;
+use zoon::dominator::EventOptions;
+use zoon::events::KeyDown;
+use zoon::*;
+
+use crate::{ElementTransform, ViewBox, ViewState};
+
+/// Views, that can be recorded by `ViewHistory`.
+pub trait HistoryView: PartialEq + Clone {
+    /// Whether `self` only adapts `recorded` to a changed environment -- e.g.
+    /// a resized view port -- instead of being a view of its own. Adaptations
+    /// replace the recorded view, they cannot be undone.
+    fn is_adaptation_of(&self, _recorded: &Self) -> bool {
+        false
+    }
+
+    /// Returns `self` adapted to the environment of `current`, e.g. when
+    /// undoing to `self` after the view port has been resized.
+    fn adapted_to(&self, _current: &Self) -> Self {
+        self.clone()
+    }
+}
+
+/// The aspect ratio of a view box follows the view port's, see
+/// `sync_view_box_with_view_port`. Changing it keeps center and scale.
+impl HistoryView for ViewBox {
+    fn is_adaptation_of(&self, recorded: &Self) -> bool {
+        let (center, recorded_center) = (self.view_box().center(), recorded.view_box().center());
+        let tolerance = 1e-9 * f64::max(self.width(), self.height());
+        self.view_box().aspect_ratio() != recorded.view_box().aspect_ratio()
+            && self.content_box() == recorded.content_box()
+            && self.rotation() == recorded.rotation()
+            && self.limits() == recorded.limits()
+            && self.preserve_aspect_ratio() == recorded.preserve_aspect_ratio()
+            && relative_eq!(self.scale(), recorded.scale(), max_relative = 1e-9)
+            && (center - recorded_center).length() <= tolerance
+    }
+
+    fn adapted_to(&self, current: &Self) -> Self {
+        let mut adapted = *self;
+        let aspect_ratio = current.view_box().aspect_ratio();
+        if adapted.view_box().aspect_ratio() != aspect_ratio {
+            adapted.set_aspect_ratio(aspect_ratio);
+        }
+        adapted
+    }
+}
+
+impl HistoryView for ViewState {}
+
+impl HistoryView for ElementTransform {}
+
+/// Undo and redo for the views of a pan-zoom state, e.g. a `ViewBox` or a
+/// `ViewState`.
+///
+/// Only settled views are recorded -- see `bind` -- so a gesture or a burst of
+/// wheel events is undone at once. Adaptations to the view port, e.g. of a
+/// `ViewBox` to its aspect ratio, are not recorded as views of their own, see
+/// `HistoryView`. Undoing and redoing set the state, which any running
+/// animation or momentum takes as a cancellation.
+pub struct ViewHistory<PZ: 'static> {
+    state: Mutable<PZ>,
+    stacks: Rc<RefCell<Stacks<PZ>>>,
+    can_undo: Mutable<bool>,
+    can_redo: Mutable<bool>,
+}
+
+impl<PZ> Clone for ViewHistory<PZ> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            stacks: self.stacks.clone(),
+            can_undo: self.can_undo.clone(),
+            can_redo: self.can_redo.clone(),
+        }
+    }
+}
+
+struct Stacks<PZ> {
+    /// The oldest view first
+    undo: VecDeque<PZ>,
+    /// The view, that was undone last, last
+    redo: Vec<PZ>,
+    /// The last recorded view
+    current: PZ,
+    /// Whether a view settled since `bind`
+    has_settled: bool,
+    max_len: usize,
+}
+
+impl<PZ: HistoryView> Stacks<PZ> {
+    /// Makes `view` the current view, if it differs from it. A new view
+    /// discards the views undone before, an adaptation replaces the current
+    /// view.
+    fn record(&mut self, view: PZ) {
+        if view == self.current {
+            return;
+        }
+        if view.is_adaptation_of(&self.current) {
+            self.current = view;
+            return;
+        }
+        let previous = std::mem::replace(&mut self.current, view);
+        self.undo.push_back(previous);
+        if self.undo.len() > self.max_len {
+            self.undo.pop_front();
+        }
+        self.redo.clear();
+    }
+
+    /// Makes the previous view the current one and returns it.
+    fn undo(&mut self) -> Option<PZ> {
+        let previous = self.undo.pop_back()?.adapted_to(&self.current);
+        let undone = std::mem::replace(&mut self.current, previous.clone());
+        self.redo.push(undone);
+        Some(previous)
+    }
+
+    /// Makes the view undone last the current one and returns it.
+    fn redo(&mut self) -> Option<PZ> {
+        let next = self.redo.pop()?.adapted_to(&self.current);
+        let redone = std::mem::replace(&mut self.current, next.clone());
+        self.undo.push_back(redone);
+        Some(next)
+    }
+}
+
+impl<PZ: HistoryView + 'static> ViewHistory<PZ> {
+    /// Starts with the current view of `state`. At most `max_len` views are
+    /// kept for undoing, older ones are dropped.
+    pub fn new(state: Mutable<PZ>, max_len: usize) -> Self {
+        let current = state.get_cloned();
+        Self {
+            state,
+            stacks: Rc::new(RefCell::new(Stacks {
+                undo: VecDeque::new(),
+                redo: Vec::new(),
+                current,
+                has_settled: false,
+                max_len,
+            })),
+            can_undo: Mutable::new(false),
+            can_redo: Mutable::new(false),
+        }
+    }
+
+    /// Records the views of the state, once they settled for `debounce_ms`
+    /// milliseconds, and binds the keyboard shortcuts to `el`, which must be
+    /// focusable -- as pan-zoom elements are:
+    ///
+    /// * Ctrl + `z` undoes
+    /// * Ctrl + Shift + `z` and Ctrl + `y` redo
+    ///
+    /// Meta works instead of Ctrl, as on macOS. Recording stops, when `el` is
+    /// removed from the DOM.
+    ///
+    /// The history starts with the first settled view, so that setting up the
+    /// view -- e.g. adapting it to the view port's size -- cannot be undone.
+    pub fn bind<E: RawEl>(&self, el: E, debounce_ms: u32) -> E {
+        let history = self.clone();
+        let recording = self.clone();
+        let record_settled =
+            super::for_each_settled(self.state.signal_cloned(), debounce_ms, move |settled| {
+                recording.record_settled(settled)
+            });
+        el.update_dom_builder(move |builder| {
+            builder
+                .event_with_options(&EventOptions::preventable(), move |e: KeyDown| {
+                    if !(e.ctrl_key() || e.meta_key()) || e.alt_key() {
+                        return;
+                    }
+                    let done = match e.key().to_lowercase().as_str() {
+                        "z" if e.shift_key() => history.redo(),
+                        "z" => history.undo(),
+                        "y" => history.redo(),
+                        _ => return,
+                    };
+                    // nothing to undo must not undo the page's last input either
+                    e.prevent_default();
+                    if !done {
+                        info!("Nothing to undo or redo");
+                    }
+                })
+                .future(record_settled)
+        })
+    }
+
+    /// Changes the state to the previous view. Returns `false`, if there is
+    /// none.
+    pub fn undo(&self) -> bool {
+        self.record(self.state.get_cloned());
+        let mut stacks = self.stacks.borrow_mut();
+        let previous = match stacks.undo() {
+            Some(previous) => previous,
+            None => return false,
+        };
+        self.update_signals(&stacks);
+        drop(stacks);
+        self.state.set(previous);
+        true
+    }
+
+    /// Changes the state to the view undone last. Returns `false`, if there is
+    /// none or the view changed since.
+    pub fn redo(&self) -> bool {
+        self.record(self.state.get_cloned());
+        let mut stacks = self.stacks.borrow_mut();
+        let next = match stacks.redo() {
+            Some(next) => next,
+            None => return false,
+        };
+        self.update_signals(&stacks);
+        drop(stacks);
+        self.state.set(next);
+        true
+    }
+
+    pub fn can_undo(&self) -> impl Signal<Item = bool> {
+        self.can_undo.signal()
+    }
+
+    pub fn can_redo(&self) -> impl Signal<Item = bool> {
+        self.can_redo.signal()
+    }
+
+    /// Forgets all views but the current one.
+    pub fn clear(&self) {
+        let mut stacks = self.stacks.borrow_mut();
+        stacks.undo.clear();
+        stacks.redo.clear();
+        stacks.current = self.state.get_cloned();
+        self.update_signals(&stacks);
+    }
+
+    fn record_settled(&self, view: PZ) {
+        let mut stacks = self.stacks.borrow_mut();
+        if !stacks.has_settled {
+            stacks.has_settled = true;
+            stacks.current = view;
+            return;
+        }
+        drop(stacks);
+        self.record(view);
+    }
+
+    fn record(&self, view: PZ) {
+        let mut stacks = self.stacks.borrow_mut();
+        stacks.record(view);
+        self.update_signals(&stacks);
+    }
+
+    fn update_signals(&self, stacks: &Stacks<PZ>) {
+        self.can_undo.set_neq(!stacks.undo.is_empty());
+        self.can_redo.set_neq(!stacks.redo.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::svg::{SvgPoint, SvgRect, SvgVec};
+
+    use super::*;
+
+    impl HistoryView for i32 {}
+
+    /// A history of `state`, that has settled on its current view
+    fn settled_history(state: &Mutable<i32>, max_len: usize) -> ViewHistory<i32> {
+        let history = ViewHistory::new(state.clone(), max_len);
+        history.record_settled(state.get());
+        history
+    }
+
+    /// Sets the state to `view` and lets it settle.
+    fn settle(history: &ViewHistory<i32>, view: i32) {
+        history.state.set(view);
+        history.record_settled(view);
+    }
+
+    fn can_undo_and_redo(history: &ViewHistory<i32>) -> (bool, bool) {
+        (history.can_undo.get(), history.can_redo.get())
+    }
+
+    #[test]
+    fn the_first_settled_view_cannot_be_undone() {
+        let state = Mutable::new(0);
+        let history = ViewHistory::new(state.clone(), 10);
+        // e.g. adapting the view to the view port
+        settle(&history, 1);
+        assert_eq!(can_undo_and_redo(&history), (false, false));
+        assert!(!history.undo());
+        assert_eq!(state.get(), 1);
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let state = Mutable::new(0);
+        let history = settled_history(&state, 10);
+        settle(&history, 1);
+        settle(&history, 2);
+        assert_eq!(can_undo_and_redo(&history), (true, false));
+
+        assert!(history.undo());
+        assert_eq!(state.get(), 1);
+        assert_eq!(can_undo_and_redo(&history), (true, true));
+        assert!(history.undo());
+        assert_eq!(state.get(), 0);
+        assert_eq!(can_undo_and_redo(&history), (false, true));
+        assert!(!history.undo());
+        assert_eq!(state.get(), 0);
+
+        assert!(history.redo());
+        assert_eq!(state.get(), 1);
+        assert!(history.redo());
+        assert_eq!(state.get(), 2);
+        assert_eq!(can_undo_and_redo(&history), (true, false));
+        assert!(!history.redo());
+    }
+
+    #[test]
+    fn changes_until_settling_are_coalesced() {
+        let state = Mutable::new(0);
+        let history = settled_history(&state, 10);
+        // the frames of a gesture
+        for view in 1..=5 {
+            state.set(view);
+        }
+        history.record_settled(5);
+        assert!(history.undo());
+        assert_eq!(state.get(), 0);
+        assert_eq!(can_undo_and_redo(&history), (false, true));
+    }
+
+    #[test]
+    fn undo_records_an_unsettled_view() {
+        let state = Mutable::new(0);
+        let history = settled_history(&state, 10);
+        // e.g. undoing before the debounce time of the last change is over
+        state.set(1);
+        assert!(history.undo());
+        assert_eq!(state.get(), 0);
+        assert!(history.redo());
+        assert_eq!(state.get(), 1);
+    }
+
+    #[test]
+    fn a_new_view_clears_redo() {
+        let state = Mutable::new(0);
+        let history = settled_history(&state, 10);
+        settle(&history, 1);
+        settle(&history, 2);
+        assert!(history.undo());
+        assert_eq!(can_undo_and_redo(&history), (true, true));
+        settle(&history, 3);
+        assert_eq!(can_undo_and_redo(&history), (true, false));
+        assert!(!history.redo());
+        assert!(history.undo());
+        assert_eq!(state.get(), 1);
+    }
+
+    #[test]
+    fn the_oldest_views_are_evicted() {
+        let state = Mutable::new(0);
+        let history = settled_history(&state, 3);
+        for view in 1..=5 {
+            settle(&history, view);
+        }
+        let mut undone = Vec::new();
+        while history.undo() {
+            undone.push(state.get());
+        }
+        assert_eq!(undone, [4, 3, 2]);
+    }
+
+    #[test]
+    fn clear() {
+        let state = Mutable::new(0);
+        let history = settled_history(&state, 10);
+        settle(&history, 1);
+        settle(&history, 2);
+        assert!(history.undo());
+        history.clear();
+        assert_eq!(can_undo_and_redo(&history), (false, false));
+        assert!(!history.undo());
+        assert!(!history.redo());
+        assert_eq!(state.get(), 1);
+    }
+
+    fn view_box(width: f64, height: f64) -> ViewBox {
+        let content_box = SvgRect::new(SvgPoint::new(-50.0, -50.0), SvgVec::new(100.0, 100.0));
+        let mut view_box = ViewBox::new(content_box, content_box);
+        view_box.set_aspect_ratio(width / height);
+        view_box
+    }
+
+    #[test]
+    fn aspect_ratio_syncs_are_not_recorded() {
+        let mut stacks = Stacks {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            current: view_box(100.0, 100.0),
+            has_settled: true,
+            max_len: 10,
+        };
+        let mut zoomed = stacks.current;
+        zoomed.set_scale(2.0);
+        stacks.record(zoomed);
+        // the view port becomes wider
+        let mut synced = zoomed;
+        synced.set_aspect_ratio(2.0);
+        assert!(synced.is_adaptation_of(&zoomed));
+        stacks.record(synced);
+        assert_eq!(stacks.undo.len(), 1);
+        assert_eq!(stacks.current, synced);
+
+        // the view undone to has the current aspect ratio
+        let undone = stacks.undo().unwrap();
+        assert_relative_eq!(undone.scale(), 1.0);
+        assert_relative_eq!(undone.view_box().aspect_ratio(), 2.0);
+        let redone = stacks.redo().unwrap();
+        assert_eq!(redone, synced);
+    }
+
+    #[test]
+    fn zooming_is_no_adaptation() {
+        let recorded = view_box(100.0, 100.0);
+        let mut zoomed = view_box(200.0, 100.0);
+        zoomed.set_scale(2.0);
+        assert!(!zoomed.is_adaptation_of(&recorded));
+        let mut panned = view_box(200.0, 100.0);
+        panned.set_top_left(panned.top_left() + SvgVec::new(10.0, 0.0));
+        assert!(!panned.is_adaptation_of(&recorded));
+        assert!(view_box(200.0, 100.0).is_adaptation_of(&recorded));
+    }
+}